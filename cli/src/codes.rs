@@ -0,0 +1,131 @@
+//! Checks that the error codes given with `#[catalog(code = "...")]` are unique
+//! across every crate of a workspace
+//!
+//! The codes of the definition files read by `error_chain_from_file!` are checked
+//! along with the ones of the `.rs` files.
+//!
+//! Besides the `check-codes` command, the check can run as a test of any crate
+//! of the workspace, with this crate as a dev-dependency:
+//!
+//! ```no_run
+//! // In tests/error_codes.rs, the workspace root being the parent directory
+//! error_chain_utils_cli::codes::assert_unique(concat!(env!("CARGO_MANIFEST_DIR"), "/.."));
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use crate::export::{self, Catalog};
+
+/// An error given a code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeUse {
+    pub file: String,
+    pub line: usize,
+    pub error_type: String,
+    pub variant: String
+}
+
+/// A code given to more than one error
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Collision {
+    pub code: String,
+    pub uses: Vec<CodeUse>
+}
+
+impl fmt::Display for Collision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "error code '{}' is used by {} errors:", self.code, self.uses.len())?;
+        for code_use in &self.uses {
+            write!(f, "\n    {}:{}: {}::{}", code_use.file, code_use.line, code_use.error_type, code_use.variant)?;
+        }
+        Ok(())
+    }
+}
+
+/// Codes of the catalog given to more than one error, sorted by code
+pub fn collisions(catalog: &Catalog) -> Vec<Collision> {
+    let mut uses: BTreeMap<&str, Vec<CodeUse>> = BTreeMap::new();
+    for error_type in &catalog.types {
+        for error in &error_type.errors {
+            if let Some(ref code) = error.code {
+                uses.entry(code).or_default().push(CodeUse {
+                    file: error_type.file.clone(),
+                    line: error.line,
+                    error_type: error_type.error.clone(),
+                    variant: error.name.clone()
+                });
+            }
+        }
+    }
+    uses.into_iter()
+        .filter(|(_, uses)| uses.len() > 1)
+        .map(|(code, uses)| Collision { code: code.to_string(), uses })
+        .collect()
+}
+
+/// Checks the `.rs` files under `root`, and panics with a report of the collisions
+/// and of the files that could not be read or parsed, if any
+pub fn assert_unique(root: impl AsRef<Path>) {
+    let (catalog, errors) = export::scan_paths(&[PathBuf::from(root.as_ref())]);
+    let mut report: Vec<String> = errors;
+    report.extend(collisions(&catalog).iter().map(ToString::to_string));
+    if !report.is_empty() {
+        panic!("Error codes are not unique:\n{}", report.join("\n"));
+    }
+}
+
+
+#[cfg(test)]
+mod tests{
+    use std::{assert_eq, env, fs};
+    use crate::codes::{assert_unique, collisions};
+    use crate::export::{scan_source, Catalog};
+
+    #[test]
+    pub fn test_collisions() {
+        let mut catalog = Catalog { types: scan_source(r#"
+error_chain_quick! {
+    errors {
+        #[catalog(code = "E0001")]
+        quick!(NotFound, "Not found")
+        #[catalog(code = "E0002")]
+        quick!(Denied, "Denied")
+    }
+}
+"#, "a/src/lib.rs").unwrap() };
+        catalog.types.append(&mut scan_source(r#"
+error_chain! {
+    types { OtherError, OtherErrorKind, OtherResultExt; }
+    errors {
+        #[catalog(code = "E0001")]
+        Missing {
+            description("Missing")
+        }
+    }
+}
+"#, "b/src/lib.rs").unwrap());
+        let collisions = collisions(&catalog);
+        assert_eq!(collisions.len(), 1);
+        assert_eq!(collisions[0].to_string(), "error code 'E0001' is used by 2 errors:
+    a/src/lib.rs:5: Error::NotFound
+    b/src/lib.rs:6: OtherError::Missing");
+    }
+
+    #[test]
+    pub fn test_assert_unique() {
+        let dir = env::temp_dir().join(format!("error-chain-utils-codes-{}", std::process::id()));
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::create_dir_all(dir.join("target")).unwrap();
+        let source = "error_chain_quick! { errors { #[catalog(code = \"E0001\")] quick!(NotFound, \"Not found\") } }\n";
+        fs::write(dir.join("src/lib.rs"), source).unwrap();
+        // Build output is not part of the workspace
+        fs::write(dir.join("target/lib.rs"), source).unwrap();
+        assert_unique(&dir);
+        fs::write(dir.join("src/other.rs"), source).unwrap();
+        let result = std::panic::catch_unwind(|| assert_unique(&dir));
+        fs::remove_dir_all(&dir).unwrap();
+        let message = *result.unwrap_err().downcast::<String>().unwrap();
+        assert!(message.starts_with("Error codes are not unique:\nerror code 'E0001' is used by 2 errors:\n"), "{}", message);
+    }
+}
@@ -0,0 +1,269 @@
+//! Compares two error catalogs, from exports or from git revisions of a crate,
+//! and tells which changes break the clients matching on the errors
+//!
+//! Removed errors and links, changed arguments, changed or removed codes and
+//! reworded display messages are breaking. Added errors, links and codes, and
+//! changes of the description, severity or help are not.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use crate::export::{self, Catalog, ErrorType, Variant};
+
+/// What changed about an error, or about a whole error type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeKind {
+    Removed,
+    Added,
+    /// Arguments, as `name: Type`
+    Args(Vec<String>, Vec<String>),
+    Code(Option<String>, Option<String>),
+    Display(String, String),
+    Description(String, String),
+    Severity(Option<String>, Option<String>),
+    Help(Option<String>, Option<String>)
+}
+
+impl ChangeKind {
+    pub fn is_breaking(&self) -> bool {
+        match self {
+            ChangeKind::Removed | ChangeKind::Args(..) | ChangeKind::Display(..) => true,
+            ChangeKind::Code(ref old, _) => old.is_some(),
+            ChangeKind::Added | ChangeKind::Description(..) | ChangeKind::Severity(..) | ChangeKind::Help(..) => false
+        }
+    }
+}
+
+fn optional(value: &Option<String>) -> String {
+    match value {
+        Some(ref val) => format!("{:?}", val),
+        None => "none".to_string()
+    }
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChangeKind::Removed => f.write_str("removed"),
+            ChangeKind::Added => f.write_str("added"),
+            ChangeKind::Args(ref old, ref new) => write!(f, "arguments changed from ({}) to ({})", old.join(", "), new.join(", ")),
+            ChangeKind::Code(ref old, ref new) => write!(f, "code changed from {} to {}", optional(old), optional(new)),
+            ChangeKind::Display(ref old, ref new) => write!(f, "message changed from {:?} to {:?}", old, new),
+            ChangeKind::Description(ref old, ref new) => write!(f, "description changed from {:?} to {:?}", old, new),
+            ChangeKind::Severity(ref old, ref new) => write!(f, "severity changed from {} to {}", optional(old), optional(new)),
+            ChangeKind::Help(ref old, ref new) => write!(f, "help changed from {} to {}", optional(old), optional(new))
+        }
+    }
+}
+
+/// A change of an error, a link, or a whole error type when `item` is `None`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub error_type: String,
+    pub item: Option<String>,
+    pub kind: ChangeKind
+}
+
+impl Change {
+    pub fn is_breaking(&self) -> bool {
+        self.kind.is_breaking()
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = if self.is_breaking() { "breaking" } else { "compatible" };
+        match self.item {
+            Some(ref item) => write!(f, "{}: {}::{}: {}", label, self.error_type, item, self.kind),
+            None => write!(f, "{}: {}: {}", label, self.error_type, self.kind)
+        }
+    }
+}
+
+/// Name an error type is matched by across catalogs, qualified by its file
+/// when another type of the catalog has the same name
+fn type_key(catalog: &Catalog, error_type: &ErrorType) -> String {
+    if catalog.types.iter().filter(|other| other.error == error_type.error).count() > 1 {
+        format!("{}:{}", error_type.file, error_type.error)
+    } else {
+        error_type.error.clone()
+    }
+}
+
+fn args(variant: &Variant) -> Vec<String> {
+    variant.args.iter().map(|arg| format!("{}: {}", arg.name, arg.ty)).collect()
+}
+
+fn compare_variants(key: &str, old: &Variant, new: &Variant, changes: &mut Vec<Change>) {
+    let mut push = |kind| changes.push(Change { error_type: key.to_string(), item: Some(new.name.clone()), kind });
+    if args(old) != args(new) {
+        push(ChangeKind::Args(args(old), args(new)));
+    }
+    if old.code != new.code {
+        push(ChangeKind::Code(old.code.clone(), new.code.clone()));
+    }
+    if old.display != new.display {
+        push(ChangeKind::Display(old.display.clone(), new.display.clone()));
+    }
+    if old.description != new.description {
+        push(ChangeKind::Description(old.description.clone(), new.description.clone()));
+    }
+    if old.severity != new.severity {
+        push(ChangeKind::Severity(old.severity.clone(), new.severity.clone()));
+    }
+    if old.help != new.help {
+        push(ChangeKind::Help(old.help.clone(), new.help.clone()));
+    }
+}
+
+/// Lists the removed and added names of two lists of items
+fn compare_names<'a>(key: &str, old: impl Iterator<Item = &'a String> + Clone, new: impl Iterator<Item = &'a String> + Clone, changes: &mut Vec<Change>) {
+    for name in old.clone().filter(|name| !new.clone().any(|other| other == *name)) {
+        changes.push(Change { error_type: key.to_string(), item: Some(name.clone()), kind: ChangeKind::Removed });
+    }
+    for name in new.filter(|name| !old.clone().any(|other| other == *name)) {
+        changes.push(Change { error_type: key.to_string(), item: Some(name.clone()), kind: ChangeKind::Added });
+    }
+}
+
+/// Changes from the `old` catalog to the `new` one, in the order of the old catalog,
+/// followed by the error types that were added
+pub fn compare(old: &Catalog, new: &Catalog) -> Vec<Change> {
+    let mut changes = vec![];
+    for old_type in &old.types {
+        let key = type_key(old, old_type);
+        let new_type = match new.types.iter().find(|val| type_key(new, val) == key) {
+            Some(val) => val,
+            None => {
+                changes.push(Change { error_type: key, item: None, kind: ChangeKind::Removed });
+                continue;
+            }
+        };
+        compare_names(&key, old_type.errors.iter().map(|val| &val.name), new_type.errors.iter().map(|val| &val.name), &mut changes);
+        for old_variant in &old_type.errors {
+            if let Some(new_variant) = new_type.errors.iter().find(|val| val.name == old_variant.name) {
+                compare_variants(&key, old_variant, new_variant, &mut changes);
+            }
+        }
+        let links = |error_type: &ErrorType| error_type.links.iter().chain(&error_type.foreign_links).map(|val| val.name.clone()).collect::<Vec<_>>();
+        let (old_links, new_links) = (links(old_type), links(new_type));
+        compare_names(&key, old_links.iter(), new_links.iter(), &mut changes);
+    }
+    for new_type in &new.types {
+        let key = type_key(new, new_type);
+        if !old.types.iter().any(|val| type_key(old, val) == key) {
+            changes.push(Change { error_type: key, item: None, kind: ChangeKind::Added });
+        }
+    }
+    changes
+}
+
+fn git(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git").args(args).output().map_err(|e| format!("Could not run git: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("git {} failed: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    String::from_utf8(output.stdout).map_err(|e| format!("git {}: {}", args.join(" "), e))
+}
+
+/// The files of a git revision
+struct GitFiles<'a> {
+    rev: &'a str
+}
+
+impl export::Files for GitFiles<'_> {
+    fn exists(&self, path: &Path) -> bool {
+        git(&["cat-file", "-e", &format!("{}:./{}", self.rev, path.display())]).is_ok()
+    }
+
+    fn read(&self, path: &Path) -> Result<String, String> {
+        git(&["show", &format!("{}:./{}", self.rev, path.display())])
+    }
+}
+
+/// Collects the catalog of the `.rs` files under `paths` as they are at the git revision `rev`,
+/// along with the definition files they read
+pub fn catalog_at(rev: &str, paths: &[PathBuf]) -> Result<Catalog, String> {
+    let mut args = vec!["ls-tree", "-r", "--name-only", rev, "--"];
+    let paths: Vec<String> = paths.iter().map(|path| path.display().to_string()).collect();
+    args.extend(paths.iter().map(String::as_str));
+    let mut catalog = Catalog::default();
+    for file in git(&args)?.lines().filter(|file| Path::new(file).extension().is_some_and(|ext| ext == "rs")) {
+        let source = git(&["show", &format!("{}:./{}", rev, file)])?;
+        let mut types = export::scan_source_with(&source, file, &GitFiles { rev }).map_err(|e| {
+            let start = e.span().start();
+            format!("{}:{}:{}:{}: {}", rev, file, start.line, start.column + 1, e)
+        })?;
+        catalog.types.append(&mut types);
+    }
+    Ok(catalog)
+}
+
+
+#[cfg(test)]
+mod tests{
+    use std::assert_eq;
+    use crate::diff::compare;
+    use crate::export::{scan_source, Catalog};
+
+    fn catalog(source: &str) -> Catalog {
+        Catalog { types: scan_source(source, "src/errors.rs").unwrap() }
+    }
+
+    #[test]
+    pub fn test_compare() {
+        let old = catalog(r#"
+            error_chain_quick! {
+                foreign_links { Io(::std::io::Error); }
+                errors {
+                    #[catalog(code = "E0001")]
+                    quick!(NotFound, "Not found", (path,))
+                    #[catalog(code = "E0002")]
+                    quick!(Denied, "Denied")
+                    quick!(Removed, "Removed")
+                    quick!(Coded, "Coded")
+                }
+            }
+        "#);
+        let new = catalog(r#"
+            error_chain_quick! {
+                errors {
+                    #[catalog(code = "E0001", severity = "warning")]
+                    quick!(NotFound, "Not found", (path, line))
+                    #[catalog(code = "E0003")]
+                    quick!(Denied, "Access denied")
+                    #[catalog(code = "E0004")]
+                    quick!(Coded, "Coded")
+                    quick!(Added, "Added")
+                }
+            }
+            mod other {
+                error_chain! { types { OtherError, OtherErrorKind, OtherResultExt; } }
+            }
+        "#);
+        let changes: Vec<String> = compare(&old, &new).iter().map(ToString::to_string).collect();
+        assert_eq!(changes, [
+            "breaking: Error::Removed: removed",
+            "compatible: Error::Added: added",
+            "breaking: Error::NotFound: arguments changed from (path: String) to (path: String, line: String)",
+            "breaking: Error::NotFound: message changed from \"Not found: {}\" to \"Not found: {}, {}\"",
+            "compatible: Error::NotFound: severity changed from none to \"warning\"",
+            "breaking: Error::Denied: code changed from \"E0002\" to \"E0003\"",
+            "breaking: Error::Denied: message changed from \"Denied\" to \"Access denied\"",
+            "compatible: Error::Denied: description changed from \"Denied\" to \"Access denied\"",
+            "compatible: Error::Coded: code changed from none to \"E0004\"",
+            "breaking: Error::Io: removed",
+            "compatible: OtherError: added"
+        ]);
+        assert!(compare(&new, &new).is_empty());
+    }
+
+    #[test]
+    pub fn test_compare_exports() {
+        let old = catalog("error_chain! { types { AppError, AppErrorKind, AppResultExt; } }");
+        let exported = crate::export::from_json(&crate::export::json(&old)).unwrap();
+        assert!(compare(&old, &exported).is_empty());
+        let changes: Vec<String> = compare(&old, &Catalog::default()).iter().map(ToString::to_string).collect();
+        assert_eq!(changes, ["breaking: AppError: removed"]);
+    }
+}
@@ -0,0 +1,200 @@
+//! Replaces `error_chain_quick!` invocations with the code they expand to, for dropping
+//! this crate from a codebase or inspecting what the macro generates
+
+use syn::{ItemMacro, ItemMod, parse_quote, spanned::Spanned, visit::{self, Visit}};
+use error_chain_utils_lib::expander::QuickExpander;
+use error_chain_utils_lib::pretty;
+use crate::source::{self, Edit, LineIndex};
+
+/// A definition that was left as it was
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Skipped {
+    pub line: usize,
+    pub reason: String
+}
+
+/// Outcome of the expansion of a source file
+#[derive(Debug)]
+pub struct Expansion {
+    pub source: String,
+    pub expanded: usize,
+    pub skipped: Vec<Skipped>,
+    /// Lines of the imports of `error_chain_quick` among other names, left to remove by hand
+    pub imports: Vec<usize>
+}
+
+#[derive(Default)]
+struct FindMacros<'ast> {
+    found: Vec<&'ast ItemMacro>,
+    skipped: Vec<Skipped>
+}
+
+impl<'ast> Visit<'ast> for FindMacros<'ast> {
+    fn visit_item_macro(&mut self, item: &'ast ItemMacro) {
+        if source::last_segment_is(&item.mac.path, "error_chain_quick") {
+            self.found.push(item);
+        } else if source::last_segment_is(&item.mac.path, "error_chain_from_file") {
+            self.skipped.push(Skipped {
+                line: item.mac.path.span().start().line,
+                reason: "error_chain_from_file! reads its definitions from another file, expand it by hand".to_string()
+            });
+        }
+        visit::visit_item_macro(self, item);
+    }
+
+    fn visit_item_mod(&mut self, item: &'ast ItemMod) {
+        if let Some(attr) = item.attrs.iter().find(|attr| source::last_segment_is(&attr.path, "error_chain_quick_mod")) {
+            self.skipped.push(Skipped {
+                line: attr.span().start().line,
+                reason: "#[error_chain_quick_mod] modules are not expanded, move their sections into error_chain_quick! first".to_string()
+            });
+        }
+        visit::visit_item_mod(self, item);
+    }
+}
+
+/// Byte range of an invocation, from its path to its closing delimiter or semicolon,
+/// leaving out the attributes
+pub(crate) fn invocation_range(index: &LineIndex, item: &ItemMacro) -> (usize, usize) {
+    let start = index.offset(item.mac.path.span().start());
+    let end = match item.semi_token {
+        Some(ref semi) => index.offset(semi.span().end()),
+        None => index.offset(item.mac.span().end())
+    };
+    (start, end)
+}
+
+/// Expander of the `expand` command, referring to `error_chain` itself unless given
+/// another crate, so that the expanded code no longer depends on this crate
+pub fn expander(crate_path: Option<syn::Path>) -> QuickExpander {
+    QuickExpander::new().crate_path(crate_path.unwrap_or_else(|| parse_quote!(::error_chain)))
+}
+
+/// Expands every `error_chain_quick!` invocation of a source file with `expander`
+///
+/// Only the invocations are replaced, the code and comments around them are kept as they are,
+/// but for the `use` items importing `error_chain_quick` alone, which are removed.
+/// `#[error_chain_quick_mod]` modules and `error_chain_from_file!` invocations are skipped.
+pub fn expand_source(source: &str, expander: &mut QuickExpander) -> syn::Result<Expansion> {
+    let file = syn::parse_file(source)?;
+    let mut finder = FindMacros::default();
+    finder.visit_file(&file);
+    let index = LineIndex::new(source);
+    let mut edits = vec![];
+    for item in &finder.found {
+        let output = pretty::expansion(expander.expand(item.mac.tokens.clone())?)?;
+        let (start, end) = invocation_range(&index, item);
+        let text = source::indent_tail(&output, source::indentation(source, start));
+        edits.push(Edit { start, end, text });
+    }
+    let mut imports = vec![];
+    if !finder.found.is_empty() {
+        let (mut import_edits, kept) = source::remove_import(&file, source, &index, "error_chain_quick");
+        edits.append(&mut import_edits);
+        imports = kept;
+    }
+    Ok(Expansion {
+        source: source::apply(source, edits),
+        expanded: finder.found.len(),
+        skipped: finder.skipped,
+        imports
+    })
+}
+
+
+#[cfg(test)]
+mod tests{
+    use std::assert_eq;
+    use syn::parse_quote;
+    use error_chain_utils_lib::expander::QuickExpander;
+    use error_chain_utils_lib::options::Backend;
+    use crate::expand::{expand_source, expander, Skipped};
+
+    #[test]
+    pub fn test_expand() {
+        let source = r#"use error_chain_utils::error_chain_quick;
+
+mod errors {
+    // Errors of the crate
+    error_chain_quick! {
+        errors {
+            quick!(NotFound, "Not found", (path,))
+            Custom(code: u32) {
+                description("Custom")
+                display("Custom: {}", code)
+            }
+        }
+    }
+}
+
+// Not an invocation
+fn main() {}
+"#;
+        let expected = r#"
+mod errors {
+    // Errors of the crate
+    ::error_chain::error_chain! {
+        errors {
+            NotFound(path: String) {
+                description("Not found")
+                display("Not found: {}", path)
+            }
+            Custom(code: u32) {
+                description("Custom")
+                display("Custom: {}", code)
+            }
+        }
+    }
+}
+
+// Not an invocation
+fn main() {}
+"#;
+        let expansion = expand_source(source, &mut expander(None)).unwrap();
+        assert_eq!(expansion.source, expected);
+        assert_eq!(expansion.expanded, 1);
+        assert!(!expansion.source.contains("error_chain_utils"));
+
+        let expansion = expand_source(source, &mut expander(Some(parse_quote!(::my_error_chain)))).unwrap();
+        assert!(expansion.source.contains("    ::my_error_chain::error_chain! {\n"));
+    }
+
+    #[test]
+    pub fn test_skipped() {
+        let source = r#"use error_chain_utils::{error_chain_from_file, error_chain_quick, error_chain_quick_mod};
+
+error_chain_quick! { errors { quick!(NotFound, "Not found") } }
+
+error_chain_from_file!("errors.toml");
+
+#[error_chain_quick_mod]
+mod errors {
+    quick!(Denied, "Denied");
+}
+"#;
+        let expansion = expand_source(source, &mut QuickExpander::new()).unwrap();
+        assert_eq!(expansion.expanded, 1);
+        assert!(expansion.source.starts_with("use error_chain_utils::{error_chain_from_file, error_chain_quick, error_chain_quick_mod};\n"));
+        assert!(expansion.source.contains("error_chain_from_file!(\"errors.toml\");\n\n#[error_chain_quick_mod]\n"));
+        assert_eq!(expansion.skipped, vec![
+            Skipped { line: 5, reason: "error_chain_from_file! reads its definitions from another file, expand it by hand".to_string() },
+            Skipped { line: 7, reason: "#[error_chain_quick_mod] modules are not expanded, move their sections into error_chain_quick! first".to_string() }
+        ]);
+        assert_eq!(expansion.imports, vec![1]);
+    }
+
+    #[test]
+    pub fn test_expand_backend() {
+        let source = "error_chain_quick!(errors { quick!(NotFound, \"Not found\") });\nfn main() {}\n";
+        let expansion = expand_source(source, &mut QuickExpander::new().backend(Backend::Thiserror)).unwrap();
+        assert!(expansion.source.starts_with("/// The Error type.\n#[derive(Debug, ::thiserror::Error)]\npub enum Error {\n"));
+        assert!(expansion.source.ends_with(";\nfn main() {}\n"));
+        assert!(!expansion.source.contains("error_chain_quick"));
+    }
+
+    #[test]
+    pub fn test_expand_error() {
+        let source = "error_chain_quick! { errors { quick!(NotFound) } }\n";
+        assert!(expand_source(source, &mut QuickExpander::new()).is_err());
+    }
+}
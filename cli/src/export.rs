@@ -0,0 +1,460 @@
+//! Collects the errors defined by the `error_chain_quick!` and `error_chain!` blocks,
+//! the `#[error_chain_quick_mod]` modules and the definition files of `error_chain_from_file!`
+//! of a crate into a catalog, written out as JSON or as a Markdown reference page
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use proc_macro2::{Ident, TokenStream};
+use serde::{Deserialize, Serialize};
+use syn::{ItemMod, LitStr, Macro, visit::{self, Visit}};
+use error_chain_utils_lib::{catalog, from_file, pretty, quick_mod};
+use error_chain_utils_lib::quick::{self, RootElementEnum, RootElementVec};
+use crate::source;
+
+/// Every error type found, in the order of the files and of the blocks within them
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Catalog {
+    pub types: Vec<ErrorType>
+}
+
+/// The errors of a single block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorType {
+    /// File the errors are written in, the definition file for `error_chain_from_file!`
+    pub file: String,
+    pub line: usize,
+    /// Names given in the `types` section, or the default ones
+    pub error: String,
+    pub error_kind: String,
+    pub backend: String,
+    pub links: Vec<Link>,
+    pub foreign_links: Vec<Link>,
+    pub errors: Vec<Variant>
+}
+
+/// A `links` or `foreign_links` entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Link {
+    pub name: String,
+    pub error: String
+}
+
+/// An error of the `errors` section, with its `#[catalog(...)]` metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Variant {
+    pub name: String,
+    /// Line of the name, left out of comparisons
+    #[serde(default)]
+    pub line: usize,
+    pub description: String,
+    /// Format string of the display, the description when the error has none
+    pub display: String,
+    pub args: Vec<Arg>,
+    pub code: Option<String>,
+    pub severity: Option<String>,
+    pub help: Option<String>
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Arg {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: String
+}
+
+/// Access to the files around the scanned sources, for the definition files of `error_chain_from_file!`
+pub trait Files {
+    fn exists(&self, path: &Path) -> bool;
+    fn read(&self, path: &Path) -> Result<String, String>;
+}
+
+/// The files of the working tree
+pub struct Disk;
+
+impl Files for Disk {
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    fn read(&self, path: &Path) -> Result<String, String> {
+        fs::read_to_string(path).map_err(|e| e.to_string())
+    }
+}
+
+/// Path of a file given to `error_chain_from_file!` in `file`, relative to the
+/// directory of the manifest of the crate `file` belongs to
+fn definition_path(files: &dyn Files, file: &str, path: &str) -> PathBuf {
+    let path = Path::new(path);
+    if path.is_absolute() {
+        return path.to_path_buf();
+    }
+    let dir = Path::new(file).ancestors().skip(1).find(|dir| files.exists(&dir.join("Cargo.toml")));
+    dir.unwrap_or_else(|| Path::new("")).join(path)
+}
+
+/// The definitions of a block, along with where they are written
+pub(crate) struct Block {
+    pub file: String,
+    /// Path of the module the definitions are in within the file, empty at the top level
+    pub module: String,
+    pub line: usize,
+    pub root: RootElementVec,
+    /// Positions in the definition file of `error_chain_from_file!`, whose tokens carry none
+    definitions: Option<from_file::Positions>
+}
+
+impl Block {
+    /// Line of the name of an error
+    pub fn line_of(&self, ident: &Ident) -> usize {
+        match self.definitions {
+            Some(ref positions) => {
+                // The errors are converted in the order of the file
+                let index = self.root.items().iter()
+                    .find_map(|item| match item {
+                        RootElementEnum::ErrorsIdGroup(ref val) => Some(val),
+                        _ => None
+                    })
+                    .and_then(|errors| errors.items().iter().position(|child| child.ident() == ident));
+                index.and_then(|index| positions.error_name(index)).map_or(1, |(line, _)| line)
+            },
+            None => ident.span().start().line
+        }
+    }
+}
+
+enum Found<'ast> {
+    Macro(&'ast Macro),
+    Module(&'ast ItemMod, TokenStream),
+    DefinitionFile(&'ast Macro)
+}
+
+#[derive(Default)]
+struct FindBlocks<'ast> {
+    /// Blocks along with the path of their module
+    found: Vec<(Found<'ast>, String)>,
+    modules: Vec<String>
+}
+
+impl<'ast> Visit<'ast> for FindBlocks<'ast> {
+    fn visit_macro(&mut self, mac: &'ast Macro) {
+        if source::last_segment_is(&mac.path, "error_chain_quick") || source::last_segment_is(&mac.path, "error_chain") {
+            self.found.push((Found::Macro(mac), self.modules.join("::")));
+        } else if source::last_segment_is(&mac.path, "error_chain_from_file") {
+            self.found.push((Found::DefinitionFile(mac), self.modules.join("::")));
+        }
+        visit::visit_macro(self, mac);
+    }
+
+    fn visit_item_mod(&mut self, item: &'ast ItemMod) {
+        self.modules.push(item.ident.to_string());
+        if let Some(attr) = item.attrs.iter().find(|attr| source::last_segment_is(&attr.path, "error_chain_quick_mod")) {
+            // The arguments of the attribute, without their parentheses
+            let args = match syn::parse2::<proc_macro2::Group>(attr.tokens.clone()) {
+                Ok(group) => group.stream(),
+                Err(_) => TokenStream::new()
+            };
+            self.found.push((Found::Module(item, args), self.modules.join("::")));
+        }
+        visit::visit_item_mod(self, item);
+        self.modules.pop();
+    }
+}
+
+/// Parses the definitions of a source file, `file` being its name: the `error_chain_quick!`
+/// and `error_chain!` invocations, nested ones included, the `#[error_chain_quick_mod]`
+/// modules, and the definition files of `error_chain_from_file!`, read from `files`
+pub(crate) fn blocks(source: &str, file: &str, files: &dyn Files) -> syn::Result<Vec<Block>> {
+    let parsed_file = syn::parse_file(source)?;
+    let mut finder = FindBlocks::default();
+    finder.visit_file(&parsed_file);
+    let mut blocks = vec![];
+    for (found, module) in finder.found {
+        let block = match found {
+            Found::Macro(mac) => Block {
+                file: file.to_string(),
+                module,
+                line: mac.path.segments[0].ident.span().start().line,
+                root: quick::parse(mac.tokens.clone())?,
+                definitions: None
+            },
+            Found::Module(item, args) => Block {
+                file: file.to_string(),
+                module,
+                line: item.ident.span().start().line,
+                root: quick_mod::parse_module(args, item)?,
+                definitions: None
+            },
+            Found::DefinitionFile(mac) => {
+                let lit = mac.parse_body::<LitStr>()?;
+                let path = definition_path(files, file, &lit.value());
+                let error = |message: String| syn::Error::new(lit.span(), format!("{}: {}", path.display(), message));
+                let format = from_file::Format::from_path(&path).ok_or_else(|| error("Expected a .toml, .yaml or .yml file".to_string()))?;
+                let text = files.read(&path).map_err(error)?;
+                let root = from_file::parse_str(&text, format, &path).map_err(|e| syn::Error::new(lit.span(), e))?;
+                let positions = from_file::Positions::new(&text, format);
+                Block { file: path.display().to_string(), module, line: 1, root, definitions: Some(positions) }
+            }
+        };
+        blocks.push(block);
+    }
+    Ok(blocks)
+}
+
+/// Names of the error and error kind types of a block, given in `types` or the default ones
+pub(crate) fn type_names(root: &RootElementVec) -> (String, String) {
+    for item in root.items() {
+        if let RootElementEnum::TypesIdGroup(ref val) = item {
+            if let Some(names) = val.names() {
+                return (names.error.to_string(), names.error_kind.to_string());
+            }
+        }
+    }
+    ("Error".to_string(), "ErrorKind".to_string())
+}
+
+/// Collects the error types of a source file, `file` being the name they are listed under,
+/// with the definition files of `error_chain_from_file!` read from `files`
+pub fn scan_source_with(source: &str, file: &str, files: &dyn Files) -> syn::Result<Vec<ErrorType>> {
+    let mut types = vec![];
+    for block in blocks(source, file, files)? {
+        let root = &block.root;
+        let (error, error_kind) = type_names(root);
+        let mut error_type = ErrorType {
+            file: block.file.clone(),
+            line: block.line,
+            error,
+            error_kind,
+            backend: root.options().backend().name().to_string(),
+            links: vec![],
+            foreign_links: vec![],
+            errors: vec![]
+        };
+        for item in root.items() {
+            match item {
+                RootElementEnum::LinksIdGroup(ref val) => error_type.links.extend(val.links().iter().map(|link| Link {
+                    name: link.variant.to_string(),
+                    error: pretty::ty(&link.error_path)
+                })),
+                RootElementEnum::ForeignLinksIdGroup(ref val) => error_type.foreign_links.extend(val.links().iter().map(|link| Link {
+                    name: link.variant.to_string(),
+                    error: pretty::ty(&link.error_path)
+                })),
+                _ => {}
+            }
+        }
+        let value = |lit: Option<syn::LitStr>| lit.map(|val| val.value());
+        for entry in catalog::entries(root)? {
+            error_type.errors.push(Variant {
+                name: entry.ident.to_string(),
+                line: block.line_of(&entry.ident),
+                description: entry.description,
+                display: entry.display,
+                args: entry.args.iter().map(|(ident, ty)| Arg { name: ident.to_string(), ty: pretty::ty(ty) }).collect(),
+                code: value(entry.metadata.code),
+                severity: value(entry.metadata.severity),
+                help: value(entry.metadata.help)
+            });
+        }
+        types.push(error_type);
+    }
+    Ok(types)
+}
+
+/// Collects the error types of a source file, `file` being its path
+pub fn scan_source(source: &str, file: &str) -> syn::Result<Vec<ErrorType>> {
+    scan_source_with(source, file, &Disk)
+}
+
+/// Collects the error types of the `.rs` files under `paths`, along with the
+/// files that could not be read or parsed, as `file:line:column: message`
+pub fn scan_paths(paths: &[PathBuf]) -> (Catalog, Vec<String>) {
+    let (types, errors) = source::scan_files(paths, &scan_source);
+    (Catalog { types }, errors)
+}
+
+/// Inline code span, fenced with enough backticks for the text
+fn code(text: &str) -> String {
+    let mut fence = "`".to_string();
+    while text.contains(fence.as_str()) {
+        fence.push('`');
+    }
+    let padding = if text.starts_with('`') || text.ends_with('`') { " " } else { "" };
+    format!("{}{}{}{}{}", fence, padding, text, padding, fence)
+}
+
+/// Renders the catalog as a Markdown page, one section per error type
+pub fn markdown(catalog: &Catalog) -> String {
+    let mut output = "# Errors\n".to_string();
+    for error_type in &catalog.types {
+        output.push_str(&format!("\n## {}\n\n", code(&error_type.error)));
+        output.push_str(&format!("Defined in {} at line {}, with the kind {}.\n", code(&error_type.file), error_type.line, code(&error_type.error_kind)));
+        for error in &error_type.errors {
+            output.push_str(&format!("\n### {}\n\n{}\n\n", code(&error.name), error.description));
+            output.push_str(&format!("- Message: {}\n", code(&error.display)));
+            if !error.args.is_empty() {
+                let args: Vec<String> = error.args.iter().map(|arg| code(&format!("{}: {}", arg.name, arg.ty))).collect();
+                output.push_str(&format!("- Arguments: {}\n", args.join(", ")));
+            }
+            if let Some(ref val) = error.code {
+                output.push_str(&format!("- Code: {}\n", code(val)));
+            }
+            if let Some(ref val) = error.severity {
+                output.push_str(&format!("- Severity: {}\n", val));
+            }
+            if let Some(ref val) = error.help {
+                output.push_str(&format!("\n{}\n", val));
+            }
+        }
+        for (title, links) in [("Links", &error_type.links), ("Foreign links", &error_type.foreign_links)] {
+            if links.is_empty() {
+                continue;
+            }
+            output.push_str(&format!("\n### {}\n\n", title));
+            for link in links {
+                output.push_str(&format!("- {}: {}\n", code(&link.name), code(&link.error)));
+            }
+        }
+    }
+    output
+}
+
+/// Renders the catalog as pretty-printed JSON
+pub fn json(catalog: &Catalog) -> String {
+    let mut output = serde_json::to_string_pretty(catalog).expect("Catalogs only hold strings and numbers");
+    output.push('\n');
+    output
+}
+
+/// Reads back a catalog written by [`json`]
+pub fn from_json(text: &str) -> serde_json::Result<Catalog> {
+    serde_json::from_str(text)
+}
+
+
+#[cfg(test)]
+mod tests{
+    use std::{assert_eq, env, fs};
+    use crate::export::{json, markdown, scan_paths, scan_source, Catalog};
+
+    const SOURCE: &str = r#"use error_chain_utils::error_chain_quick;
+
+error_chain_quick! {
+    types {
+        AppError, AppErrorKind, AppResultExt;
+    }
+
+    foreign_links {
+        Io(::std::io::Error);
+    }
+
+    errors {
+        #[catalog(code = "E0001", severity = "warning", help = "Check the path.")]
+        quick!(NotFound, "Not found", (path,))
+    }
+}
+
+mod other {
+    error_chain! {
+        errors {
+            Custom(code: u32, names: Vec<String>) {
+                description("Custom")
+                display("Custom error `{}`", code)
+            }
+        }
+    }
+}
+"#;
+
+    #[test]
+    pub fn test_scan() {
+        let types = scan_source(SOURCE, "src/errors.rs").unwrap();
+        assert_eq!(types.len(), 2);
+        assert_eq!((types[0].error.as_str(), types[0].error_kind.as_str(), types[0].line), ("AppError", "AppErrorKind", 3));
+        assert_eq!(types[0].foreign_links[0].error, "::std::io::Error");
+        let not_found = &types[0].errors[0];
+        assert_eq!((not_found.name.as_str(), not_found.display.as_str()), ("NotFound", "Not found: {}"));
+        assert_eq!(not_found.code.as_deref(), Some("E0001"));
+        assert_eq!((types[1].error.as_str(), types[1].line), ("Error", 19));
+        assert_eq!(types[1].errors[0].args[1].ty, "Vec<String>");
+
+        assert!(scan_source("error_chain_quick! { errors { quick!(NotFound) } }", "src/lib.rs").is_err());
+    }
+
+    #[test]
+    pub fn test_scan_modules_and_files() {
+        let dir = env::temp_dir().join(format!("error-chain-utils-export-{}", std::process::id()));
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("Cargo.toml"), "[package]\nname = \"app\"\n").unwrap();
+        fs::write(dir.join("errors.toml"), "[types]\nerror = \"FileError\"\nerror_kind = \"FileErrorKind\"\nresult_ext = \"FileResultExt\"\n\n[[errors]]\nname = \"Missing\"\ndescription = \"Missing\"\ncode = \"E0002\"\n").unwrap();
+        fs::write(dir.join("src/lib.rs"), r#"#[error_chain_quick_mod(backend = thiserror)]
+mod errors {
+    quick!(NotFound, "Not found", (path,));
+}
+
+mod from_file {
+    error_chain_from_file!("errors.toml");
+}
+"#).unwrap();
+        let (catalog, errors) = scan_paths(std::slice::from_ref(&dir));
+        fs::write(dir.join("src/lib.rs"), "error_chain_from_file!(\"missing.toml\");\n").unwrap();
+        let (_, missing) = scan_paths(std::slice::from_ref(&dir));
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(catalog.types.len(), 2);
+        assert_eq!((catalog.types[0].line, catalog.types[0].backend.as_str()), (2, "thiserror"));
+        assert_eq!((catalog.types[0].errors[0].name.as_str(), catalog.types[0].errors[0].line), ("NotFound", 3));
+        let file_type = &catalog.types[1];
+        assert_eq!(file_type.file, dir.join("errors.toml").display().to_string());
+        assert_eq!(file_type.error, "FileError");
+        assert_eq!((file_type.errors[0].line, file_type.errors[0].code.as_deref()), (7, Some("E0002")));
+        assert_eq!(missing.len(), 1);
+        assert!(missing[0].contains("missing.toml: "), "{}", missing[0]);
+    }
+
+    #[test]
+    pub fn test_markdown() {
+        let catalog = Catalog { types: scan_source(SOURCE, "src/errors.rs").unwrap() };
+        let expected = r#"# Errors
+
+## `AppError`
+
+Defined in `src/errors.rs` at line 3, with the kind `AppErrorKind`.
+
+### `NotFound`
+
+Not found
+
+- Message: `Not found: {}`
+- Arguments: `path: String`
+- Code: `E0001`
+- Severity: warning
+
+Check the path.
+
+### Foreign links
+
+- `Io`: `::std::io::Error`
+
+## `Error`
+
+Defined in `src/errors.rs` at line 19, with the kind `ErrorKind`.
+
+### `Custom`
+
+Custom
+
+- Message: `` Custom error `{}` ``
+- Arguments: `code: u32`, `names: Vec<String>`
+"#;
+        assert_eq!(markdown(&catalog), expected);
+    }
+
+    #[test]
+    pub fn test_json() {
+        let catalog = Catalog { types: scan_source(SOURCE, "src/errors.rs").unwrap() };
+        let output = json(&catalog);
+        assert!(output.starts_with("{\n  \"types\": [\n    {\n      \"file\": \"src/errors.rs\",\n      \"line\": 3,\n"));
+        assert!(output.contains("\"args\": [\n            {\n              \"name\": \"path\",\n              \"type\": \"String\"\n            }\n          ],\n"));
+        assert!(output.contains("\"code\": null,"));
+    }
+}
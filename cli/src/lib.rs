@@ -0,0 +1,11 @@
+//! Source rewriting and error catalog tools for `error-chain-utils`, behind the
+//! `error-chain-utils` binary
+
+pub mod codes;
+pub mod diff;
+pub mod expand;
+pub mod export;
+pub mod messages;
+pub mod migrate;
+pub mod source;
+pub mod to_thiserror;
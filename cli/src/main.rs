@@ -0,0 +1,282 @@
+//! Command line interface of `error-chain-utils`
+
+use std::{env, fs, process};
+use std::path::{Path, PathBuf};
+use error_chain_utils_cli::{codes, diff, expand, export, messages, migrate, source, to_thiserror};
+
+const USAGE: &str = "Usage: error-chain-utils <command> [options] <paths>...
+
+Commands:
+    migrate         Rewrite error_chain! blocks into error_chain_quick! ones
+    expand          Replace error_chain_quick! blocks with the code they expand to
+    to-thiserror    Rewrite error_chain! and error_chain_quick! blocks into thiserror
+                    enums, along with bail!, ensure! and ErrorKind conversions
+    export          List the errors of error_chain_quick! and error_chain! blocks,
+                    #[error_chain_quick_mod] modules and error_chain_from_file!
+                    definition files in a Markdown page and a JSON document
+    diff            Compare two JSON exports, or with --git two revisions of the
+                    given paths, and fail on changes breaking the errors' clients
+    check-codes     Fail when an error code is given to more than one error, the
+                    paths being the root of a workspace or its crates
+    extract-messages
+                    Write the descriptions and display messages of the errors
+                    to a gettext template or a Fluent resource, for translators
+
+Options:
+    --dry-run       Print a diff of the changes instead of writing them
+    --crate <path>  Crate of the expanded error_chain! invocations, for expand,
+                    ::error_chain by default
+    --markdown <file>, --json <file>
+                    Files export writes the catalog to, - being the standard output
+    --git           Take the git revisions to compare instead of exports, for diff
+    --pot <file>, --ftl <file>
+                    Files extract-messages writes the messages to
+
+Directories are searched recursively for .rs files.";
+
+/// Outcome of a transformation of a file: the new source, and notes about it
+type Rewrite = (String, Vec<String>);
+
+/// Where `export` writes the catalog and `extract-messages` the messages
+#[derive(Default)]
+struct Outputs {
+    markdown: Option<PathBuf>,
+    json: Option<PathBuf>,
+    pot: Option<PathBuf>,
+    ftl: Option<PathBuf>
+}
+
+/// Runs `transform` over every file, writing the result or printing a diff of it,
+/// and returns whether all the files could be processed
+fn rewrite(paths: &[PathBuf], dry_run: bool, transform: &dyn Fn(&str) -> syn::Result<Rewrite>) -> bool {
+    let mut files = vec![];
+    for path in paths {
+        if let Err(e) = source::rust_files(path, &mut files) {
+            eprintln!("{}: {}", path.display(), e);
+            return false;
+        }
+    }
+    let mut success = true;
+    for file in files {
+        let name = file.display().to_string();
+        let result = fs::read_to_string(&file)
+            .map_err(|e| e.to_string())
+            .and_then(|old| transform(&old).map(|val| (old, val)).map_err(|e| {
+                let start = e.span().start();
+                format!("{}:{}: {}", start.line, start.column + 1, e)
+            }));
+        let (old, (new, notes)) = match result {
+            Ok(val) => val,
+            Err(e) => {
+                eprintln!("{}:{}", name, e);
+                success = false;
+                continue;
+            }
+        };
+        for note in notes {
+            eprintln!("{}:{}", name, note);
+        }
+        if old == new {
+            continue;
+        }
+        if dry_run {
+            print!("{}", source::diff(&name, &old, &new));
+        } else if let Err(e) = fs::write(&file, new) {
+            eprintln!("{}: {}", name, e);
+            success = false;
+        }
+    }
+    success
+}
+
+/// Writes to a file, or to the standard output for `-`, and returns whether it could
+fn write_output(path: &Path, output: &str) -> bool {
+    if path.as_os_str() == "-" {
+        print!("{}", output);
+        return true;
+    }
+    match fs::write(path, output) {
+        Ok(()) => true,
+        Err(e) => {
+            eprintln!("{}: {}", path.display(), e);
+            false
+        }
+    }
+}
+
+/// Collects the errors of every file and writes the catalog to the outputs,
+/// and returns whether all the files could be processed
+fn export(paths: &[PathBuf], outputs: &Outputs) -> bool {
+    let (catalog, errors) = export::scan_paths(paths);
+    for e in &errors {
+        eprintln!("{}", e);
+    }
+    let mut success = errors.is_empty();
+    if let Some(ref path) = outputs.markdown {
+        success &= write_output(path, &export::markdown(&catalog));
+    }
+    if let Some(ref path) = outputs.json {
+        success &= write_output(path, &export::json(&catalog));
+    }
+    success
+}
+
+/// Extracts the messages of every file and writes them to the outputs,
+/// and returns whether all the files could be processed
+fn extract_messages(paths: &[PathBuf], outputs: &Outputs) -> bool {
+    let (found, errors) = source::scan_files(paths, &messages::extract_source);
+    for e in &errors {
+        eprintln!("{}", e);
+    }
+    let mut success = errors.is_empty();
+    if let Some(ref path) = outputs.pot {
+        success &= write_output(path, &messages::pot(&found));
+    }
+    if let Some(ref path) = outputs.ftl {
+        success &= write_output(path, &messages::ftl(&found));
+    }
+    success
+}
+
+/// Loads the catalogs to compare, from exports or from git revisions
+fn diff_catalogs(args: &[PathBuf], git: bool) -> Result<(export::Catalog, export::Catalog), String> {
+    if git {
+        let (revs, paths) = args.split_at(2.min(args.len()));
+        let paths = if paths.is_empty() { vec![PathBuf::from(".")] } else { paths.to_vec() };
+        match revs {
+            [old, new] => Ok((diff::catalog_at(&old.to_string_lossy(), &paths)?, diff::catalog_at(&new.to_string_lossy(), &paths)?)),
+            _ => usage_error("diff --git needs two revisions")
+        }
+    } else {
+        let load = |path: &PathBuf| fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| export::from_json(&text).map_err(|e| e.to_string()))
+            .map_err(|e| format!("{}: {}", path.display(), e));
+        match args {
+            [old, new] => Ok((load(old)?, load(new)?)),
+            _ => usage_error("diff needs two exports")
+        }
+    }
+}
+
+/// Prints the changes between the catalogs, and returns whether none of them is breaking
+fn diff(args: &[PathBuf], git: bool) -> bool {
+    let (old, new) = match diff_catalogs(args, git) {
+        Ok(val) => val,
+        Err(e) => {
+            eprintln!("{}", e);
+            return false;
+        }
+    };
+    let changes = diff::compare(&old, &new);
+    for change in &changes {
+        println!("{}", change);
+    }
+    !changes.iter().any(diff::Change::is_breaking)
+}
+
+/// Prints the codes given to more than one error, and returns whether there are none
+fn check_codes(paths: &[PathBuf]) -> bool {
+    let (catalog, errors) = export::scan_paths(paths);
+    for e in &errors {
+        eprintln!("{}", e);
+    }
+    let collisions = codes::collisions(&catalog);
+    for collision in &collisions {
+        println!("{}", collision);
+    }
+    errors.is_empty() && collisions.is_empty()
+}
+
+fn migrate_file(source: &str) -> syn::Result<Rewrite> {
+    let migration = migrate::migrate_source(source)?;
+    let mut notes: Vec<String> = migration.skipped.into_iter().map(|skipped| match skipped.variant {
+        Some(variant) => format!("{}: skipped '{}': {}", skipped.line, variant, skipped.reason),
+        None => format!("{}: skipped error_chain! invocation: {}", skipped.line, skipped.reason)
+    }).collect();
+    notes.extend(migration.imports.into_iter().map(|line| format!("{}: error_chain is no longer used, remove it from the import", line)));
+    Ok((migration.source, notes))
+}
+
+fn to_thiserror_file(source: &str) -> syn::Result<Rewrite> {
+    let migration = to_thiserror::migrate_source(source)?;
+    let notes = migration.todos.into_iter().map(|todo| format!("{}: TODO(migrate): {}", todo.line, todo.reason)).collect();
+    Ok((migration.source, notes))
+}
+
+fn expand_file(source: &str, crate_path: Option<&syn::Path>) -> syn::Result<Rewrite> {
+    let expansion = expand::expand_source(source, &mut expand::expander(crate_path.cloned()))?;
+    let mut notes: Vec<String> = expansion.skipped.into_iter().map(|skipped| format!("{}: skipped: {}", skipped.line, skipped.reason)).collect();
+    notes.extend(expansion.imports.into_iter().map(|line| format!("{}: error_chain_quick is no longer used, remove it from the import", line)));
+    Ok((expansion.source, notes))
+}
+
+/// Prints the usage after the message and exits with the status of usage errors
+fn usage_error(message: &str) -> ! {
+    eprintln!("{}\n\n{}", message, USAGE);
+    process::exit(2);
+}
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let command = match args.next() {
+        Some(val) if val != "-h" && val != "--help" => val,
+        _ => {
+            println!("{}", USAGE);
+            return;
+        }
+    };
+    let mut dry_run = false;
+    let mut git = false;
+    let mut crate_path: Option<syn::Path> = None;
+    let mut outputs = Outputs::default();
+    let mut paths = vec![];
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dry-run" => dry_run = true,
+            "--git" => git = true,
+            "--crate" => match args.next().map(|val| syn::parse_str(&val)) {
+                Some(Ok(val)) => crate_path = Some(val),
+                Some(Err(e)) => usage_error(&format!("Invalid crate path: {}", e)),
+                None => usage_error("Missing the path of --crate")
+            },
+            "--markdown" => outputs.markdown = Some(args.next().map(PathBuf::from).unwrap_or_else(|| usage_error("Missing the file of --markdown"))),
+            "--json" => outputs.json = Some(args.next().map(PathBuf::from).unwrap_or_else(|| usage_error("Missing the file of --json"))),
+            "--pot" => outputs.pot = Some(args.next().map(PathBuf::from).unwrap_or_else(|| usage_error("Missing the file of --pot"))),
+            "--ftl" => outputs.ftl = Some(args.next().map(PathBuf::from).unwrap_or_else(|| usage_error("Missing the file of --ftl"))),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            },
+            val if val.starts_with('-') => usage_error(&format!("Unknown option '{}'", val)),
+            val => paths.push(PathBuf::from(val))
+        }
+    }
+    if paths.is_empty() {
+        usage_error("No paths given");
+    }
+    let exporting = outputs.markdown.is_some() || outputs.json.is_some();
+    let extracting = outputs.pot.is_some() || outputs.ftl.is_some();
+    let success = match command.as_str() {
+        "migrate" | "to-thiserror" | "export" | "extract-messages" if crate_path.is_some() => usage_error("--crate is only supported by expand"),
+        "migrate" | "to-thiserror" | "expand" | "extract-messages" if exporting => usage_error("--markdown and --json are only supported by export"),
+        "migrate" | "to-thiserror" | "expand" | "export" if extracting => usage_error("--pot and --ftl are only supported by extract-messages"),
+        "diff" if crate_path.is_some() || exporting || extracting || dry_run => usage_error("diff only supports --git"),
+        "diff" => diff(&paths, git),
+        _ if git => usage_error("--git is only supported by diff"),
+        "check-codes" if crate_path.is_some() || exporting || extracting || dry_run => usage_error("check-codes takes no options"),
+        "check-codes" => check_codes(&paths),
+        "export" | "extract-messages" if dry_run => usage_error(&format!("--dry-run is not supported by {}", command)),
+        "export" if !exporting => usage_error("export needs --markdown or --json"),
+        "export" => export(&paths, &outputs),
+        "extract-messages" if !extracting => usage_error("extract-messages needs --pot or --ftl"),
+        "extract-messages" => extract_messages(&paths, &outputs),
+        "migrate" => rewrite(&paths, dry_run, &migrate_file),
+        "to-thiserror" => rewrite(&paths, dry_run, &to_thiserror_file),
+        "expand" => rewrite(&paths, dry_run, &|source| expand_file(source, crate_path.as_ref())),
+        other => usage_error(&format!("Unknown command '{}'", other))
+    };
+    if !success {
+        process::exit(1);
+    }
+}
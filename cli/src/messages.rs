@@ -0,0 +1,348 @@
+//! Extracts the descriptions and display messages of the errors, for translation,
+//! into a gettext template or a Fluent resource
+//!
+//! Messages are keyed by the name of the error type and of the variant, the type being
+//! qualified by its module, and then by its file, when several types share its name. Only
+//! string literals are extracted, the messages built from other expressions are
+//! left out.
+
+use syn::{Expr, ExprLit, ExprPath, Lit};
+use error_chain_utils_lib::quick::{ErrorsChildElementEnum, RootElementEnum};
+use error_chain_utils_lib::quick::errors_child_element::NormalError;
+use crate::export::{self, Block};
+
+/// Which message of an error is extracted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageKind {
+    Description,
+    /// The format string of `display(...)`
+    Display
+}
+
+impl MessageKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            MessageKind::Description => "description",
+            MessageKind::Display => "display"
+        }
+    }
+}
+
+/// A message of an error
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub error_type: String,
+    /// Path of the module of the error type within its file, empty at the top level
+    pub module: String,
+    pub variant: String,
+    pub kind: MessageKind,
+    pub text: String,
+    /// Names of the format arguments of a display, `argN` for the ones that are not plain names
+    pub args: Vec<String>,
+    pub file: String,
+    pub line: usize
+}
+
+fn string_lit(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Lit(ExprLit { lit: Lit::Str(ref val), .. }) => Some(val.value()),
+        _ => None
+    }
+}
+
+fn arg_name(index: usize, expr: &Expr) -> String {
+    match expr {
+        Expr::Path(ExprPath { qself: None, ref path, .. }) => match path.get_ident() {
+            Some(ident) => ident.to_string(),
+            None => format!("arg{}", index)
+        },
+        _ => format!("arg{}", index)
+    }
+}
+
+fn error_messages(error_type: &str, error: &NormalError, block: &Block, messages: &mut Vec<Message>) {
+    let message = |kind, text, args| Message {
+        error_type: error_type.to_string(),
+        module: block.module.clone(),
+        variant: error.ident().to_string(),
+        kind,
+        text,
+        args,
+        file: block.file.clone(),
+        line: block.line_of(error.ident())
+    };
+    if let Some(text) = error.description().and_then(|val| string_lit(&val.expr)) {
+        messages.push(message(MessageKind::Description, text, vec![]));
+    }
+    if let Some(display) = error.display() {
+        // The format string of `display(self_) -> ("...", args)` comes after the arrow
+        let args = match display.chained {
+            Some((_, _, ref args)) => args,
+            None => &display.args
+        };
+        if let Some(text) = args.first().and_then(string_lit) {
+            let names = args.iter().skip(1).enumerate().map(|(index, arg)| arg_name(index, arg)).collect();
+            messages.push(message(MessageKind::Display, text, names));
+        }
+    }
+}
+
+/// Extracts the messages of a source file, `file` being its path
+pub fn extract_source(source: &str, file: &str) -> syn::Result<Vec<Message>> {
+    let mut messages = vec![];
+    for block in export::blocks(source, file, &export::Disk)? {
+        let root = &block.root;
+        let (error_type, _) = export::type_names(root);
+        for item in root.items() {
+            let errors = match item {
+                RootElementEnum::ErrorsIdGroup(ref val) => val,
+                _ => continue
+            };
+            for child in errors.items() {
+                match child {
+                    ErrorsChildElementEnum::QuickError(ref val) => {
+                        let error = val.clone().into_normal_with(root.options());
+                        error_messages(&error_type, &error, &block, &mut messages);
+                    },
+                    ErrorsChildElementEnum::NormalError(ref val) => error_messages(&error_type, val, &block, &mut messages)
+                }
+            }
+        }
+    }
+    Ok(messages)
+}
+
+/// Quoted string of a `.pot` file
+fn po_string(text: &str) -> String {
+    let mut output = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\t' => output.push_str("\\t"),
+            c => output.push(c)
+        }
+    }
+    output.push('"');
+    output
+}
+
+/// Name the messages of an error type are keyed by, qualified by its module, and
+/// then by its file, when messages of other error types of the same name are listed
+fn type_key(messages: &[Message], message: &Message) -> String {
+    let others: Vec<&Message> = messages.iter()
+        .filter(|other| other.error_type == message.error_type && (other.file != message.file || other.module != message.module))
+        .collect();
+    if others.is_empty() {
+        return message.error_type.clone();
+    }
+    let qualified = if message.module.is_empty() {
+        message.error_type.clone()
+    } else {
+        format!("{}::{}", message.module, message.error_type)
+    };
+    if others.iter().all(|other| other.module != message.module) {
+        qualified
+    } else {
+        format!("{}:{}", message.file, qualified)
+    }
+}
+
+/// Renders the messages as a gettext template, the context of each being
+/// `Type.Variant.description` or `Type.Variant.display`
+pub fn pot(messages: &[Message]) -> String {
+    let mut output = "msgid \"\"\nmsgstr \"\"\n\"Content-Type: text/plain; charset=UTF-8\\n\"\n".to_string();
+    for message in messages {
+        output.push('\n');
+        if !message.args.is_empty() {
+            output.push_str(&format!("#. Arguments: {}\n", message.args.join(", ")));
+        }
+        output.push_str(&format!("#: {}:{}\n", message.file, message.line));
+        let context = format!("{}.{}.{}", type_key(messages, message), message.variant, message.kind.name());
+        output.push_str(&format!("msgctxt {}\nmsgid {}\nmsgstr \"\"\n", po_string(&context), po_string(&message.text)));
+    }
+    output
+}
+
+/// Text of a Fluent pattern, with the placeholders of a format string turned
+/// into variables when `args` is given
+fn fluent_pattern(text: &str, args: Option<&[String]>) -> String {
+    let mut output = String::new();
+    let mut chars = text.chars().peekable();
+    let mut next_arg = 0;
+    while let Some(c) = chars.next() {
+        match c {
+            '{' | '}' if args.is_none() => output.push_str(&format!("{{\"{}\"}}", c)),
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                output.push_str("{\"{\"}");
+            },
+            '}' => {
+                if chars.peek() == Some(&'}') {
+                    chars.next();
+                }
+                output.push_str("{\"}\"}");
+            },
+            '{' => {
+                let mut placeholder = String::new();
+                for c in chars.by_ref().take_while(|c| *c != '}') {
+                    placeholder.push(c);
+                }
+                let name = placeholder.split(':').next().unwrap_or_default().trim();
+                let args = args.unwrap_or_default();
+                let name = if name.is_empty() {
+                    next_arg += 1;
+                    args.get(next_arg - 1).cloned().unwrap_or_else(|| format!("arg{}", next_arg - 1))
+                } else if let Ok(index) = name.parse::<usize>() {
+                    args.get(index).cloned().unwrap_or_else(|| format!("arg{}", index))
+                } else {
+                    name.to_string()
+                };
+                output.push_str(&format!("{{ ${} }}", name));
+            },
+            '\n' => output.push_str("\n    "),
+            c => output.push(c)
+        }
+    }
+    output
+}
+
+/// Identifier of the Fluent message of an error, the characters Fluent does not
+/// allow in identifiers being replaced
+fn fluent_id(messages: &[Message], message: &Message) -> String {
+    let id = format!("{}-{}", type_key(messages, message).replace("::", "-"), message.variant);
+    let id: String = id.chars().map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' }).collect();
+    id.trim_start_matches(|c: char| !c.is_ascii_alphabetic()).to_string()
+}
+
+/// Renders the messages as a Fluent resource, with a `Type-Variant` message per error
+/// whose value is the display and whose `description` attribute is the description
+pub fn ftl(messages: &[Message]) -> String {
+    let mut output = String::new();
+    let mut index = 0;
+    while index < messages.len() {
+        let first = &messages[index];
+        let count = messages[index..].iter()
+            .take_while(|val| val.file == first.file && val.module == first.module && val.error_type == first.error_type && val.variant == first.variant && val.line == first.line)
+            .count();
+        let group = &messages[index..index + count];
+        index += count;
+        if !output.is_empty() {
+            output.push('\n');
+        }
+        output.push_str(&format!("# {}:{}\n{} =", first.file, first.line, fluent_id(messages, first)));
+        if let Some(display) = group.iter().find(|val| val.kind == MessageKind::Display) {
+            output.push_str(&format!(" {}", fluent_pattern(&display.text, Some(&display.args))));
+        }
+        output.push('\n');
+        if let Some(description) = group.iter().find(|val| val.kind == MessageKind::Description) {
+            output.push_str(&format!("    .description = {}\n", fluent_pattern(&description.text, None)));
+        }
+    }
+    output
+}
+
+
+#[cfg(test)]
+mod tests{
+    use std::assert_eq;
+    use crate::messages::{extract_source, ftl, pot};
+
+    const SOURCE: &str = r#"
+error_chain_quick! {
+    types {
+        AppError, AppErrorKind, AppResultExt;
+    }
+
+    errors {
+        quick!(NotFound, "Not \"found\"", (path,))
+        Custom(code: u32) {
+            description("Custom {code}")
+            display("Custom error {:?} ({{{}}})", code, code + 1)
+        }
+        Dynamic {
+            description(DESCRIPTION)
+        }
+    }
+}
+"#;
+
+    #[test]
+    pub fn test_pot() {
+        let messages = extract_source(SOURCE, "src/errors.rs").unwrap();
+        assert_eq!(messages.len(), 4);
+        let expected = r#"msgid ""
+msgstr ""
+"Content-Type: text/plain; charset=UTF-8\n"
+
+#: src/errors.rs:8
+msgctxt "AppError.NotFound.description"
+msgid "Not \"found\""
+msgstr ""
+
+#. Arguments: path
+#: src/errors.rs:8
+msgctxt "AppError.NotFound.display"
+msgid "Not \"found\": {}"
+msgstr ""
+
+#: src/errors.rs:9
+msgctxt "AppError.Custom.description"
+msgid "Custom {code}"
+msgstr ""
+
+#. Arguments: code, arg1
+#: src/errors.rs:9
+msgctxt "AppError.Custom.display"
+msgid "Custom error {:?} ({{{}}})"
+msgstr ""
+"#;
+        assert_eq!(pot(&messages), expected);
+    }
+
+    #[test]
+    pub fn test_colliding_types() {
+        let source = r#"
+mod io {
+    error_chain! { errors { NotFound { description("Not found") } } }
+}
+mod net {
+    error_chain! { errors { NotFound { description("Not found") } } }
+}
+error_chain! { types { AppError, AppErrorKind, AppResultExt; } errors { NotFound { description("Not found") } } }
+"#;
+        let mut messages = extract_source(source, "src/a.rs").unwrap();
+        messages.append(&mut extract_source(source, "src/b.rs").unwrap());
+        let output = pot(&messages);
+        let contexts: Vec<&str> = output.lines().filter(|line| line.starts_with("msgctxt ")).collect();
+        assert_eq!(contexts, [
+            "msgctxt \"src/a.rs:io::Error.NotFound.description\"",
+            "msgctxt \"src/a.rs:net::Error.NotFound.description\"",
+            "msgctxt \"src/a.rs:AppError.NotFound.description\"",
+            "msgctxt \"src/b.rs:io::Error.NotFound.description\"",
+            "msgctxt \"src/b.rs:net::Error.NotFound.description\"",
+            "msgctxt \"src/b.rs:AppError.NotFound.description\""
+        ]);
+
+        let messages = extract_source(source, "src/a.rs").unwrap();
+        let output = ftl(&messages);
+        assert!(output.contains("\nio-Error-NotFound =\n"), "{}", output);
+        assert!(output.contains("\nnet-Error-NotFound =\n"), "{}", output);
+        assert!(output.contains("\nAppError-NotFound =\n"), "{}", output);
+    }
+
+    #[test]
+    pub fn test_ftl() {
+        let messages = extract_source(SOURCE, "src/errors.rs").unwrap();
+        let expected = r#"# src/errors.rs:8
+AppError-NotFound = Not "found": { $path }
+    .description = Not "found"
+
+# src/errors.rs:9
+AppError-Custom = Custom error { $code } ({"{"}{ $arg1 }{"}"})
+    .description = Custom {"{"}code{"}"}
+"#;
+        assert_eq!(ftl(&messages), expected);
+    }
+}
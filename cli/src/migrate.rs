@@ -0,0 +1,268 @@
+//! Rewrites `error_chain!` invocations into `error_chain_quick!` ones, turning every
+//! error that follows the `quick!` pattern into a `quick!` entry
+
+use proc_macro2::Span;
+use quote::ToTokens;
+use syn::{Expr, ExprLit, Lit, LitStr, Macro, Type, spanned::Spanned, visit::{self, Visit}};
+use error_chain_utils_lib::options::DisplayStyle;
+use error_chain_utils_lib::quick::{self, ErrorsChildElementEnum, RootElementEnum};
+use error_chain_utils_lib::quick::errors_child_element::NormalError;
+use crate::source::{self, Edit, LineIndex};
+
+/// Path the rewritten invocations refer to, which needs no import
+const QUICK_PATH: &str = "error_chain_utils::error_chain_quick";
+
+/// An error, or a whole invocation, that was left as it was
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Skipped {
+    pub line: usize,
+    /// Name of the error, `None` when the whole invocation was skipped
+    pub variant: Option<String>,
+    pub reason: String
+}
+
+/// Outcome of the migration of a source file
+#[derive(Debug)]
+pub struct Migration {
+    pub source: String,
+    pub converted: usize,
+    pub skipped: Vec<Skipped>,
+    /// Lines of the imports of `error_chain` among other names, left to remove by hand
+    pub imports: Vec<usize>
+}
+
+fn string_lit(expr: &Expr) -> Option<&LitStr> {
+    match expr {
+        Expr::Lit(ExprLit { lit: Lit::Str(ref val), .. }) => Some(val),
+        _ => None
+    }
+}
+
+fn is_string(ty: &Type) -> bool {
+    match ty {
+        Type::Path(ref val) => val.qself.is_none() && val.path.is_ident("String"),
+        _ => false
+    }
+}
+
+/// Returns the `quick!` entry equivalent to the error, its attributes left out, or why there is none
+pub fn quick_form(error: &NormalError) -> Result<String, String> {
+    let mut names = vec![];
+    for arg in error.args().into_iter().flatten() {
+        if !is_string(&arg.ty) {
+            return Err(format!("argument '{}' is a {}, quick! arguments are Strings", arg.ident, arg.ty.to_token_stream()));
+        }
+        names.push(arg.ident.to_string());
+    }
+    let description = match error.description() {
+        Some(val) => match string_lit(&val.expr) {
+            Some(lit) => lit,
+            None => return Err("the description is not a string literal".to_string())
+        },
+        None => return Err("it has no description".to_string())
+    };
+    let expected = DisplayStyle::Colon.format(&description.value(), &names);
+    let display_matches = match error.display() {
+        Some(display) if display.chained.is_some() => false,
+        Some(display) => {
+            let mut args = display.args.iter();
+            let format_matches = matches!(args.next().and_then(string_lit), Some(lit) if lit.value() == expected);
+            let args: Vec<String> = args.map(|arg| arg.to_token_stream().to_string()).collect();
+            format_matches && args == names
+        },
+        // `error_chain!` displays the description by default
+        None => names.is_empty()
+    };
+    if !display_matches {
+        return Err("its display does not follow the quick! format".to_string());
+    }
+    let description = description.to_token_stream().to_string();
+    Ok(if names.is_empty() {
+        format!("quick!({}, {})", error.ident(), description)
+    } else {
+        format!("quick!({}, {}, ({}))", error.ident(), description, names.join(", "))
+    })
+}
+
+#[derive(Default)]
+struct FindMacros<'ast> {
+    found: Vec<&'ast Macro>
+}
+
+impl<'ast> Visit<'ast> for FindMacros<'ast> {
+    fn visit_macro(&mut self, mac: &'ast Macro) {
+        if source::last_segment_is(&mac.path, "error_chain") {
+            self.found.push(mac);
+        }
+        visit::visit_macro(self, mac);
+    }
+}
+
+fn line(span: Span) -> usize {
+    span.start().line
+}
+
+/// Migrates every `error_chain!` invocation of a source file
+///
+/// Invocations where no error could be converted are left untouched.
+pub fn migrate_source(source: &str) -> syn::Result<Migration> {
+    let file = syn::parse_file(source)?;
+    let mut finder = FindMacros::default();
+    finder.visit_file(&file);
+    let index = LineIndex::new(source);
+    let mut edits = vec![];
+    let mut converted = 0;
+    let mut skipped = vec![];
+    let mut remaining = 0;
+    for mac in &finder.found {
+        // Invocations left as they are still need the import of `error_chain!`
+        let still_imported = mac.path.is_ident("error_chain");
+        let parsed = match quick::parse(mac.tokens.clone()) {
+            Ok(val) => val,
+            Err(e) => {
+                skipped.push(Skipped { line: line(e.span()), variant: None, reason: e.to_string() });
+                remaining += usize::from(still_imported);
+                continue;
+            }
+        };
+        let mut macro_edits = vec![];
+        for item in parsed.items() {
+            let errors = match item {
+                RootElementEnum::ErrorsIdGroup(val) => val,
+                _ => continue
+            };
+            for child in errors.items() {
+                let error = match child {
+                    ErrorsChildElementEnum::NormalError(val) => val,
+                    ErrorsChildElementEnum::QuickError(_) => continue
+                };
+                match quick_form(error) {
+                    Ok(text) => {
+                        // The attributes, doc comments included, are kept as written
+                        let start = index.offset(error.ident().span().start());
+                        let (_, end) = index.range(error.span());
+                        macro_edits.push(Edit { start, end, text });
+                    },
+                    Err(reason) => skipped.push(Skipped {
+                        line: line(error.ident().span()),
+                        variant: Some(error.ident().to_string()),
+                        reason
+                    })
+                }
+            }
+        }
+        if macro_edits.is_empty() {
+            remaining += usize::from(still_imported);
+            continue;
+        }
+        converted += macro_edits.len();
+        let (start, end) = index.range(mac.path.span());
+        edits.push(Edit { start, end, text: QUICK_PATH.to_string() });
+        edits.append(&mut macro_edits);
+    }
+    let mut imports = vec![];
+    if converted > 0 && remaining == 0 {
+        let (mut import_edits, kept) = source::remove_import(&file, source, &index, "error_chain");
+        edits.append(&mut import_edits);
+        imports = kept;
+    }
+    Ok(Migration {
+        source: source::apply(source, edits),
+        converted,
+        skipped,
+        imports
+    })
+}
+
+
+#[cfg(test)]
+mod tests{
+    use std::assert_eq;
+    use crate::migrate::{migrate_source, Skipped};
+
+    #[test]
+    pub fn test_migrate() {
+        let source = r#"use error_chain::error_chain;
+
+// Errors of the crate
+error_chain! {
+    types {
+        BuildError, BEKind, BETrait, BEResult;
+    }
+
+    errors {
+        /// Documented
+        Documented {
+            description("Documented")
+            display("Documented")
+        }
+        NoArgs {
+            description("No arguments"),
+            display("No arguments"),
+        }
+        // One argument
+        OneArg(path: String) {
+            description("One argument")
+            display("One argument: {}", path)
+        }
+        Custom(code: u32) {
+            description("Custom")
+            display("Custom: {}", code)
+        }
+        OnlyDescription {
+            description("Only description")
+        }
+    }
+}
+"#;
+        let expected = r#"
+// Errors of the crate
+error_chain_utils::error_chain_quick! {
+    types {
+        BuildError, BEKind, BETrait, BEResult;
+    }
+
+    errors {
+        /// Documented
+        quick!(Documented, "Documented")
+        quick!(NoArgs, "No arguments")
+        // One argument
+        quick!(OneArg, "One argument", (path))
+        Custom(code: u32) {
+            description("Custom")
+            display("Custom: {}", code)
+        }
+        quick!(OnlyDescription, "Only description")
+    }
+}
+"#;
+        let migration = migrate_source(source).unwrap();
+        assert_eq!(migration.source, expected);
+        assert_eq!(migration.converted, 4);
+        assert_eq!(migration.skipped, vec![
+            Skipped { line: 24, variant: Some("Custom".to_string()), reason: "argument 'code' is a u32, quick! arguments are Strings".to_string() }
+        ]);
+    }
+
+    #[test]
+    pub fn test_imports() {
+        // The import is still needed by the invocation that could not be converted
+        let source = "use error_chain::error_chain;\nerror_chain! { errors { A { description(\"A\") } } }\nmod other {\n    error_chain! { errors { B(code: u32) { description(\"B\") } } }\n}\n";
+        let migration = migrate_source(source).unwrap();
+        assert!(migration.source.starts_with("use error_chain::error_chain;\n"));
+
+        let source = "use error_chain::{bail, error_chain};\nerror_chain! { errors { A { description(\"A\") } } }\n";
+        let migration = migrate_source(source).unwrap();
+        assert!(migration.source.starts_with("use error_chain::{bail, error_chain};\n"));
+        assert_eq!(migration.imports, vec![1]);
+    }
+
+    #[test]
+    pub fn test_unchanged() {
+        let source = "fn main() {\n    error_chain! { errors { Foo(code: u32) { description(\"Foo\") } } }\n}\n";
+        let migration = migrate_source(source).unwrap();
+        assert_eq!(migration.source, source);
+        assert_eq!(migration.converted, 0);
+        assert_eq!(migration.skipped.len(), 1);
+    }
+}
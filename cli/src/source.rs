@@ -0,0 +1,192 @@
+//! Helpers for rewriting parts of a source file, located by the spans of its tokens
+
+use std::{fs, io};
+use std::path::{Path, PathBuf};
+use proc_macro2::{LineColumn, Span};
+use syn::{ItemUse, UseTree, spanned::Spanned, visit::{self, Visit}};
+
+/// Collects the `.rs` files under `path`, or `path` itself when it is a file
+///
+/// Build output and hidden directories, such as `target` and `.git`, are skipped
+/// unless given directly.
+pub fn rust_files(path: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    if path.is_dir() {
+        let mut entries = fs::read_dir(path)?.collect::<io::Result<Vec<_>>>()?;
+        entries.sort_by_key(|entry| entry.path());
+        for entry in entries {
+            let path = entry.path();
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if (path.is_dir() && name != "target" && !name.starts_with('.')) || path.extension().is_some_and(|ext| ext == "rs") {
+                rust_files(&path, files)?;
+            }
+        }
+    } else {
+        files.push(path.to_path_buf());
+    }
+    Ok(())
+}
+
+/// Runs `scan` over the `.rs` files under `paths` and collects what it finds, along with
+/// the files that could not be read or parsed, as `file:line:column: message`
+pub fn scan_files<T>(paths: &[PathBuf], scan: &dyn Fn(&str, &str) -> syn::Result<Vec<T>>) -> (Vec<T>, Vec<String>) {
+    let mut found = vec![];
+    let mut errors = vec![];
+    let mut files = vec![];
+    for path in paths {
+        if let Err(e) = rust_files(path, &mut files) {
+            errors.push(format!("{}: {}", path.display(), e));
+        }
+    }
+    for file in files {
+        let name = file.display().to_string();
+        let result = fs::read_to_string(&file)
+            .map_err(|e| format!("{}: {}", name, e))
+            .and_then(|text| scan(&text, &name).map_err(|e| {
+                let start = e.span().start();
+                format!("{}:{}:{}: {}", name, start.line, start.column + 1, e)
+            }));
+        match result {
+            Ok(mut val) => found.append(&mut val),
+            Err(e) => errors.push(e)
+        }
+    }
+    (found, errors)
+}
+
+/// Whether the last segment of the path, such as the name of a macro however it is imported, is `name`
+pub fn last_segment_is(path: &syn::Path, name: &str) -> bool {
+    matches!(path.segments.last(), Some(segment) if segment.ident == name)
+}
+
+/// Maps the line and column of spans back to byte offsets in the source
+pub struct LineIndex<'a> {
+    source: &'a str,
+    line_starts: Vec<usize>
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(source: &'a str) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(source.match_indices('\n').map(|(index, _)| index + 1));
+        LineIndex { source, line_starts }
+    }
+
+    /// Byte offset of a line and column, the column counting characters
+    pub fn offset(&self, position: LineColumn) -> usize {
+        let start = self.line_starts[position.line - 1];
+        self.source[start..].char_indices()
+            .nth(position.column)
+            .map(|(index, _)| start + index)
+            .unwrap_or(self.source.len())
+    }
+
+    /// Byte range covered by a span
+    pub fn range(&self, span: Span) -> (usize, usize) {
+        (self.offset(span.start()), self.offset(span.end()))
+    }
+}
+
+/// Replacement of a byte range of the source
+pub struct Edit {
+    pub start: usize,
+    pub end: usize,
+    pub text: String
+}
+
+/// Applies non-overlapping edits to the source
+pub fn apply(source: &str, mut edits: Vec<Edit>) -> String {
+    edits.sort_by_key(|edit| edit.start);
+    let mut output = String::with_capacity(source.len());
+    let mut position = 0;
+    for edit in edits {
+        output.push_str(&source[position..edit.start]);
+        output.push_str(&edit.text);
+        position = edit.end;
+    }
+    output.push_str(&source[position..]);
+    output
+}
+
+/// Leading whitespace of the line holding `offset`
+pub fn indentation(source: &str, offset: usize) -> &str {
+    let start = line_start(source, offset);
+    let line = &source[start..];
+    &line[..line.len() - line.trim_start().len()]
+}
+
+/// Offset of the start of the line holding `offset`
+pub fn line_start(source: &str, offset: usize) -> usize {
+    source[..offset].rfind('\n').map_or(0, |index| index + 1)
+}
+
+/// Indents every line but the first, which is meant to follow code already indented
+pub fn indent_tail(text: &str, indent: &str) -> String {
+    text.lines().enumerate()
+        .map(|(index, line)| if index == 0 || line.is_empty() { line.to_string() } else { format!("{}{}", indent, line) })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Whether the tree imports `name` and nothing else, as `error_chain::error_chain` does
+fn imports_only(tree: &UseTree, name: &str) -> bool {
+    match tree {
+        UseTree::Path(ref val) => imports_only(&val.tree, name),
+        UseTree::Name(ref val) => val.ident == name,
+        _ => false
+    }
+}
+
+/// Whether the tree imports `name`, possibly among other names
+fn imports(tree: &UseTree, name: &str) -> bool {
+    match tree {
+        UseTree::Path(ref val) => imports(&val.tree, name),
+        UseTree::Name(ref val) => val.ident == name,
+        UseTree::Group(ref val) => val.items.iter().any(|tree| imports(tree, name)),
+        UseTree::Rename(_) | UseTree::Glob(_) => false
+    }
+}
+
+struct FindImports<'ast, 'a> {
+    name: &'a str,
+    found: Vec<&'ast ItemUse>
+}
+
+impl<'ast> Visit<'ast> for FindImports<'ast, '_> {
+    fn visit_item_use(&mut self, item: &'ast ItemUse) {
+        if imports(&item.tree, self.name) {
+            self.found.push(item);
+        }
+        visit::visit_item_use(self, item);
+    }
+}
+
+/// Edits removing the `use` items of the file that import `name` alone, along with
+/// the lines of the ones importing it among other names, which are left to the user
+pub fn remove_import(file: &syn::File, source: &str, index: &LineIndex, name: &str) -> (Vec<Edit>, Vec<usize>) {
+    let mut finder = FindImports { name, found: vec![] };
+    finder.visit_file(file);
+    let mut edits = vec![];
+    let mut kept = vec![];
+    for item in finder.found {
+        if imports_only(&item.tree, name) {
+            let (start, mut end) = index.range(item.span());
+            let start = line_start(source, start);
+            if source[end..].starts_with('\n') {
+                end += 1;
+            }
+            edits.push(Edit { start, end, text: String::new() });
+        } else {
+            kept.push(item.span().start().line);
+        }
+    }
+    (edits, kept)
+}
+
+/// Unified diff between two versions of a file
+pub fn diff(path: &str, old: &str, new: &str) -> String {
+    similar::TextDiff::from_lines(old, new)
+        .unified_diff()
+        .header(&format!("a/{}", path), &format!("b/{}", path))
+        .to_string()
+}
@@ -0,0 +1,439 @@
+//! Migrates `error_chain!` and `error_chain_quick!` definitions to `thiserror` enums,
+//! along with the call sites of the macros of `error_chain`
+//!
+//! The enums get a `Chained` variant and a `ResultExt` trait of their own, so `chain_err`
+//! calls keep working as they are. `ErrorKind::X(..).into()` becomes `Error::X(..)`,
+//! assuming the conversion was into the error type of the same definitions.
+//! What cannot be rewritten is flagged with a `// TODO(migrate)` comment.
+
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{format_ident, quote, ToTokens};
+use syn::{BinOp, Expr, ExprMethodCall, ExprUnary, Item, ItemExternCrate, ItemMacro, ItemUse, Macro, Token, UnOp, UseTree};
+use syn::{punctuated::Punctuated, spanned::Spanned, visit::{self, Visit}};
+use error_chain_utils_lib::expander::QuickExpander;
+use error_chain_utils_lib::options::{Backend, Options};
+use error_chain_utils_lib::pretty;
+use error_chain_utils_lib::quick::{self, RootElementEnum, RootElementVec};
+use crate::expand::invocation_range;
+use crate::source::{self, Edit, LineIndex};
+
+/// Something left for the user to migrate by hand
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Todo {
+    pub line: usize,
+    pub reason: String
+}
+
+/// Outcome of the migration of a source file
+#[derive(Debug)]
+pub struct Migration {
+    pub source: String,
+    /// Number of definitions turned into enums
+    pub definitions: usize,
+    /// Number of call sites rewritten
+    pub call_sites: usize,
+    pub todos: Vec<Todo>
+}
+
+/// Names of the types generated by a definition
+struct TypeNames {
+    error: Ident,
+    error_kind: Ident,
+    result_ext: Ident
+}
+
+impl TypeNames {
+    fn new(root: &RootElementVec) -> Self {
+        let names = root.items().iter().find_map(|item| match item {
+            RootElementEnum::TypesIdGroup(ref val) => val.names(),
+            _ => None
+        });
+        match names {
+            Some(names) => TypeNames {
+                error: names.error.clone(),
+                error_kind: names.error_kind.clone(),
+                result_ext: names.result_ext.clone()
+            },
+            None => TypeNames {
+                error: format_ident!("Error"),
+                error_kind: format_ident!("ErrorKind"),
+                result_ext: format_ident!("ResultExt")
+            }
+        }
+    }
+}
+
+/// The variant and trait standing in for the chaining of `error_chain`
+fn chaining(names: &TypeNames) -> (syn::Variant, Vec<Item>) {
+    let TypeNames { error, result_ext, .. } = names;
+    let variant = syn::parse_quote!{
+        /// An error along with the one that caused it, as built by `chain_err`.
+        #[error("{0}")]
+        Chained(Box<#error>, #[source] Box<dyn ::std::error::Error + Send>)
+    };
+    let signature = quote!{
+        fn chain_err<F, EK>(self, callback: F) -> ::std::result::Result<T, #error>
+        where
+            F: FnOnce() -> EK,
+            EK: Into<#error>
+    };
+    let items: syn::File = syn::parse_quote!{
+        /// Additional methods for `Result` and `Option`, in place of the ones of `error_chain`.
+        pub trait #result_ext<T> {
+            /// Replaces the error with the one returned by `callback`, keeping it as its source.
+            #signature;
+        }
+        impl<T, E: ::std::error::Error + Send + 'static> #result_ext<T> for ::std::result::Result<T, E> {
+            #signature {
+                self.map_err(|e| #error::Chained(Box::new(callback().into()), Box::new(e)))
+            }
+        }
+        impl<T> #result_ext<T> for Option<T> {
+            #signature {
+                self.ok_or_else(|| callback().into())
+            }
+        }
+    };
+    (variant, items.items)
+}
+
+/// Expands a definition into a pretty printed `thiserror` enum and its helpers
+fn definition(tokens: TokenStream) -> syn::Result<(String, TypeNames)> {
+    let mut parsed = quick::parse(tokens)?;
+    let names = TypeNames::new(&parsed);
+    // Only the options shaping the errors carry over, the crate path being the one of the old backend
+    let options = parsed.options().clone();
+    parsed.set_options(Options::default());
+    let mut expander = QuickExpander::new()
+        .backend(Backend::Thiserror)
+        .arg_type(options.arg_type())
+        .display_style(options.display_style());
+    for derive in options.derives() {
+        expander = expander.derive(derive.clone());
+    }
+    let mut file = syn::parse2::<syn::File>(expander.expand_parsed(parsed)?)?;
+    let (variant, items) = chaining(&names);
+    for item in &mut file.items {
+        if let Item::Enum(ref mut val) = item {
+            if val.ident == names.error {
+                val.variants.push(variant.clone());
+            }
+        }
+    }
+    file.items.extend(items);
+    Ok((pretty::items(file.into_token_stream())?.trim_end().to_string(), names))
+}
+
+#[derive(Default)]
+struct CallSites<'ast> {
+    definitions: Vec<&'ast ItemMacro>,
+    macros: Vec<&'ast Macro>,
+    conversions: Vec<&'ast ExprMethodCall>,
+    todos: Vec<(Span, String)>
+}
+
+impl<'ast> Visit<'ast> for CallSites<'ast> {
+    fn visit_item_macro(&mut self, item: &'ast ItemMacro) {
+        if source::last_segment_is(&item.mac.path, "error_chain") || source::last_segment_is(&item.mac.path, "error_chain_quick") {
+            self.definitions.push(item);
+        } else {
+            visit::visit_item_macro(self, item);
+        }
+    }
+
+    fn visit_macro(&mut self, mac: &'ast Macro) {
+        if source::last_segment_is(&mac.path, "bail") || source::last_segment_is(&mac.path, "ensure") {
+            self.macros.push(mac);
+        } else if source::last_segment_is(&mac.path, "quick_main") {
+            self.todos.push((mac.span(), "quick_main! has no thiserror counterpart, write the main function by hand".to_string()));
+        }
+        visit::visit_macro(self, mac);
+    }
+
+    fn visit_expr_method_call(&mut self, call: &'ast ExprMethodCall) {
+        if call.method == "into" && call.args.is_empty() {
+            self.conversions.push(call);
+        } else if call.method == "display_chain" {
+            self.todos.push((call.method.span(), "display_chain() is gone, walk the error sources instead".to_string()));
+        }
+        visit::visit_expr_method_call(self, call);
+    }
+
+    fn visit_item_use(&mut self, item: &'ast ItemUse) {
+        if matches!(item.tree, UseTree::Path(ref val) if val.ident == "error_chain") {
+            self.todos.push((item.span(), "error_chain is no longer needed once the migration is done".to_string()));
+        }
+        visit::visit_item_use(self, item);
+    }
+
+    fn visit_item_extern_crate(&mut self, item: &'ast ItemExternCrate) {
+        if item.ident == "error_chain" {
+            self.todos.push((item.span(), "error_chain is no longer needed once the migration is done".to_string()));
+        }
+        visit::visit_item_extern_crate(self, item);
+    }
+}
+
+/// Wraps the expression in parentheses when a method call would bind to a part of it
+fn receiver(expr: &Expr, text: &str) -> String {
+    match expr {
+        Expr::Lit(_) | Expr::Path(_) | Expr::Call(_) | Expr::MethodCall(_) | Expr::Macro(_) | Expr::Field(_) | Expr::Index(_) | Expr::Paren(_) => text.to_string(),
+        _ => format!("({})", text)
+    }
+}
+
+/// Negation of a condition, turning `a == b` into `a != b` and `!a` into `a` rather than
+/// wrapping them into `!(...)`
+fn negate(condition: &Expr, text: &str, source: &str, index: &LineIndex) -> String {
+    let inner = |expr: &Expr| {
+        let (start, end) = index.range(expr.span());
+        source[start..end].to_string()
+    };
+    match condition {
+        Expr::Binary(ref val) if matches!(val.op, BinOp::Eq(_) | BinOp::Ne(_)) => {
+            let op = if matches!(val.op, BinOp::Eq(_)) { "!=" } else { "==" };
+            format!("{} {} {}", inner(&val.left), op, inner(&val.right))
+        },
+        Expr::Unary(ExprUnary { op: UnOp::Not(_), ref expr, .. }) => match **expr {
+            Expr::Paren(ref val) => inner(&val.expr),
+            ref expr => inner(expr)
+        },
+        _ => format!("!{}", receiver(condition, text))
+    }
+}
+
+/// Rewrites `bail!` and `ensure!` into the code they expand to, errors given as a kind
+/// among `kinds` being built as the error type directly
+fn rewrite_macro(mac: &Macro, source: &str, index: &LineIndex, kinds: &[(Ident, Ident)]) -> Result<String, String> {
+    let name = mac.path.segments.last().unwrap().ident.to_string();
+    let args = mac.parse_body_with(Punctuated::<Expr, Token![,]>::parse_terminated)
+        .map_err(|e| format!("the arguments of {}! could not be parsed: {}", name, e))?;
+    let args: Vec<&Expr> = args.iter().collect();
+    let text = |exprs: &[&Expr]| {
+        let (start, _) = index.range(exprs[0].span());
+        let (_, end) = index.range(exprs[exprs.len() - 1].span());
+        source[start..end].to_string()
+    };
+    let error = |exprs: &[&Expr]| match exprs {
+        [] => None,
+        [expr] => Some(match kind_segment(expr, kinds) {
+            Some((kind, error)) => {
+                let (start, end) = index.range(expr.span());
+                let (kind_start, kind_end) = index.range(kind.span());
+                format!("return Err({}{}{})", &source[start..kind_start], error, &source[kind_end..end])
+            },
+            None => format!("return Err({}.into())", receiver(expr, &text(exprs)))
+        }),
+        _ => Some(format!("return Err(format!({}).into())", text(exprs)))
+    };
+    let rewritten = if name == "bail" {
+        error(&args)
+    } else {
+        match args.split_first() {
+            Some((condition, rest)) => error(rest).map(|error| format!("if {} {{ {}; }}", negate(condition, &text(&[condition]), source, index), error)),
+            None => None
+        }
+    };
+    rewritten.ok_or_else(|| format!("{}! is missing its arguments", name))
+}
+
+/// The kind segment of `Kind::Variant(..)` or `Kind::Variant`, and the error type replacing it
+fn kind_segment<'a>(expr: &'a Expr, kinds: &'a [(Ident, Ident)]) -> Option<(&'a Ident, &'a Ident)> {
+    let path = match expr {
+        Expr::Call(ref call) => match *call.func {
+            Expr::Path(ref val) => &val.path,
+            _ => return None
+        },
+        Expr::Path(ref val) => &val.path,
+        _ => return None
+    };
+    let segments: Vec<_> = path.segments.iter().collect();
+    if segments.len() < 2 {
+        return None;
+    }
+    let kind = &segments[segments.len() - 2].ident;
+    kinds.iter().find(|(val, _)| val == kind).map(|(_, error)| (kind, error))
+}
+
+/// Migrates the definitions and call sites of a source file
+pub fn migrate_source(source: &str) -> syn::Result<Migration> {
+    let file = syn::parse_file(source)?;
+    let mut sites = CallSites::default();
+    sites.visit_file(&file);
+    let index = LineIndex::new(source);
+    let mut edits = vec![];
+    let mut todos = sites.todos;
+    let mut kinds: Vec<(Ident, Ident)> = vec![];
+    let mut definitions = 0;
+    for item in &sites.definitions {
+        match definition(item.mac.tokens.clone()) {
+            Ok((output, names)) => {
+                let (start, end) = invocation_range(&index, item);
+                let text = source::indent_tail(&output, source::indentation(source, start));
+                edits.push(Edit { start, end, text });
+                kinds.push((names.error_kind, names.error));
+                definitions += 1;
+            },
+            Err(e) => todos.push((item.mac.path.span(), format!("the definitions could not be migrated: {}", e)))
+        }
+    }
+    // Definitions from other files most likely keep the default names
+    if !kinds.iter().any(|(kind, _)| kind == "ErrorKind") {
+        kinds.push((format_ident!("ErrorKind"), format_ident!("Error")));
+    }
+    let mut call_sites = 0;
+    for mac in &sites.macros {
+        match rewrite_macro(mac, source, &index, &kinds) {
+            Ok(text) => {
+                let (start, mut end) = index.range(mac.span());
+                // `ensure!` turns into an `if` block, which takes no semicolon
+                if text.starts_with("if ") && source[end..].starts_with(';') {
+                    end += 1;
+                }
+                edits.push(Edit { start, end, text });
+                call_sites += 1;
+            },
+            Err(reason) => todos.push((mac.span(), reason))
+        }
+    }
+    for call in &sites.conversions {
+        if let Some((kind, error)) = kind_segment(&call.receiver, &kinds) {
+            let (start, end) = index.range(kind.span());
+            edits.push(Edit { start, end, text: error.to_string() });
+            let (_, start) = index.range(call.receiver.span());
+            let (_, end) = index.range(call.span());
+            edits.push(Edit { start, end, text: String::new() });
+            call_sites += 1;
+        }
+    }
+    // One comment per line, above the line
+    todos.sort_by_key(|(span, _)| (span.start().line, span.start().column));
+    todos.dedup_by_key(|(span, _)| span.start().line);
+    for (span, reason) in &todos {
+        let start = source::line_start(source, index.offset(span.start()));
+        let text = format!("{}// TODO(migrate): {}\n", source::indentation(source, start), reason);
+        edits.push(Edit { start, end: start, text });
+    }
+    Ok(Migration {
+        source: source::apply(source, edits),
+        definitions,
+        call_sites,
+        todos: todos.into_iter().map(|(span, reason)| Todo { line: span.start().line, reason }).collect()
+    })
+}
+
+
+#[cfg(test)]
+mod tests{
+    use std::assert_eq;
+    use crate::to_thiserror::{migrate_source, Todo};
+
+    #[test]
+    pub fn test_definitions() {
+        let source = r#"mod errors {
+    error_chain_utils::error_chain_quick! {
+        types { BuildError, BEKind, BETrait; }
+        foreign_links { Io(::std::io::Error); }
+        errors {
+            quick!(NotFound, "Not found", (path,))
+        }
+    }
+}
+"#;
+        let expected = r#"mod errors {
+    /// The Error type.
+    #[derive(Debug, ::thiserror::Error)]
+    pub enum BuildError {
+        #[error(transparent)]
+        Io(#[from] ::std::io::Error),
+        #[error("Not found: {}", .0)]
+        NotFound(String),
+        /// A convenient variant for String.
+        #[error("{0}")]
+        Msg(String),
+        /// An error along with the one that caused it, as built by `chain_err`.
+        #[error("{0}")]
+        Chained(Box<BuildError>, #[source] Box<dyn ::std::error::Error + Send>),
+    }"#;
+        let migration = migrate_source(source).unwrap();
+        assert!(migration.source.contains(expected), "{}", migration.source);
+        assert!(migration.source.contains("    pub type BEKind = BuildError;\n"));
+        assert!(migration.source.contains("    pub trait BETrait<T> {\n"));
+        assert!(migration.source.ends_with("    }\n}\n"));
+        assert_eq!(migration.definitions, 1);
+        assert!(migration.todos.is_empty());
+    }
+
+    #[test]
+    pub fn test_call_sites() {
+        let source = r#"use error_chain::bail;
+use errors::*;
+
+fn run(path: &str) -> Result<()> {
+    if path.is_empty() {
+        bail!("No path");
+    }
+    ensure!(path != "-", "Invalid path {}", path);
+    ensure!(path.is_ascii(), "Not ASCII");
+    ensure!(!(path.len() > 1 && path.len() < 100), "Bad length");
+    let _ = std::fs::read(path).chain_err(|| ErrorKind::NotFound(path.to_string()))?;
+    Err(ErrorKind::NotFound(path.to_string()).into())
+}
+
+fn main() {
+    if let Err(e) = run("a") {
+        let chain = e.display_chain();
+        bail!();
+    }
+}
+"#;
+        let expected = r#"// TODO(migrate): error_chain is no longer needed once the migration is done
+use error_chain::bail;
+use errors::*;
+
+fn run(path: &str) -> Result<()> {
+    if path.is_empty() {
+        return Err("No path".into());
+    }
+    if path == "-" { return Err(format!("Invalid path {}", path).into()); }
+    if !path.is_ascii() { return Err("Not ASCII".into()); }
+    if path.len() > 1 && path.len() < 100 { return Err("Bad length".into()); }
+    let _ = std::fs::read(path).chain_err(|| ErrorKind::NotFound(path.to_string()))?;
+    Err(Error::NotFound(path.to_string()))
+}
+
+fn main() {
+    if let Err(e) = run("a") {
+        // TODO(migrate): display_chain() is gone, walk the error sources instead
+        let chain = e.display_chain();
+        // TODO(migrate): bail! is missing its arguments
+        bail!();
+    }
+}
+"#;
+        let migration = migrate_source(source).unwrap();
+        assert_eq!(migration.source, expected);
+        assert_eq!(migration.definitions, 0);
+        assert_eq!(migration.call_sites, 5);
+        assert_eq!(migration.todos.len(), 3);
+        assert_eq!(migration.todos[2], Todo { line: 18, reason: "bail! is missing its arguments".to_string() });
+    }
+
+    #[test]
+    pub fn test_output() {
+        // The output is compiled by the to_thiserror_output test
+        let migration = migrate_source(include_str!("../tests/fixtures/to_thiserror_input.rs")).unwrap();
+        assert_eq!(migration.source, include_str!("../tests/fixtures/to_thiserror_output.rs"));
+        assert_eq!((migration.definitions, migration.call_sites), (1, 4));
+        assert!(migration.todos.is_empty(), "{:?}", migration.todos);
+    }
+
+    #[test]
+    pub fn test_unsupported_definition() {
+        let source = "error_chain! { errors { Foo(x: u8) { display(e) -> (\"{}\", e) } } }\n";
+        let migration = migrate_source(source).unwrap();
+        assert_eq!(migration.definitions, 0);
+        assert!(migration.source.starts_with("// TODO(migrate): the definitions could not be migrated: "));
+        assert!(migration.source.ends_with(source));
+    }
+}
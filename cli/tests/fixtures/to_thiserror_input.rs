@@ -0,0 +1,37 @@
+error_chain! {
+    types {
+        AppError, AppErrorKind, AppResultExt, AppResult;
+    }
+
+    foreign_links {
+        Io(::std::io::Error);
+    }
+
+    errors {
+        NotFound(path: String) {
+            description("Not found")
+            display("Not found: {}", path)
+        }
+        Empty {
+            description("Empty file")
+        }
+    }
+}
+
+pub fn read(path: &str) -> AppResult<Vec<u8>> {
+    if path.is_empty() {
+        bail!("No path");
+    }
+    ensure!(path != "-", AppErrorKind::NotFound(path.to_string()));
+    ensure!(path.is_ascii(), "Not ASCII: {}", path);
+    let bytes = std::fs::read(path).chain_err(|| AppErrorKind::NotFound(path.to_string()))?;
+    if bytes.is_empty() {
+        return Err(AppErrorKind::Empty.into());
+    }
+    Ok(bytes)
+}
+
+pub fn first_line(path: &str) -> AppResult<String> {
+    let text = String::from_utf8(read(path)?).chain_err(|| "Not UTF-8")?;
+    text.lines().next().map(str::to_string).chain_err(|| AppErrorKind::Empty)
+}
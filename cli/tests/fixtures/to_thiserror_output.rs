@@ -0,0 +1,77 @@
+/// The Error type.
+#[derive(Debug, ::thiserror::Error)]
+pub enum AppError {
+    #[error(transparent)]
+    Io(#[from] ::std::io::Error),
+    #[error("Not found: {}", .0)]
+    NotFound(String),
+    #[error("{}", "Empty file")]
+    Empty,
+    /// A convenient variant for String.
+    #[error("{0}")]
+    Msg(String),
+    /// An error along with the one that caused it, as built by `chain_err`.
+    #[error("{0}")]
+    Chained(Box<AppError>, #[source] Box<dyn ::std::error::Error + Send>),
+}
+impl<'a> ::std::convert::From<&'a str> for AppError {
+    fn from(s: &'a str) -> Self {
+        AppError::Msg(s.into())
+    }
+}
+impl ::std::convert::From<String> for AppError {
+    fn from(s: String) -> Self {
+        AppError::Msg(s)
+    }
+}
+/// Alias of the error type, for code written against `error_chain!` kinds.
+#[allow(unused)]
+pub type AppErrorKind = AppError;
+/// Convenient wrapper around `std::Result`.
+#[allow(unused)]
+pub type AppResult<T> = ::std::result::Result<T, AppError>;
+/// Additional methods for `Result` and `Option`, in place of the ones of `error_chain`.
+pub trait AppResultExt<T> {
+    /// Replaces the error with the one returned by `callback`, keeping it as its source.
+    fn chain_err<F, EK>(self, callback: F) -> ::std::result::Result<T, AppError>
+    where
+        F: FnOnce() -> EK,
+        EK: Into<AppError>;
+}
+impl<T, E: ::std::error::Error + Send + 'static> AppResultExt<T>
+for ::std::result::Result<T, E> {
+    fn chain_err<F, EK>(self, callback: F) -> ::std::result::Result<T, AppError>
+    where
+        F: FnOnce() -> EK,
+        EK: Into<AppError>,
+    {
+        self.map_err(|e| AppError::Chained(Box::new(callback().into()), Box::new(e)))
+    }
+}
+impl<T> AppResultExt<T> for Option<T> {
+    fn chain_err<F, EK>(self, callback: F) -> ::std::result::Result<T, AppError>
+    where
+        F: FnOnce() -> EK,
+        EK: Into<AppError>,
+    {
+        self.ok_or_else(|| callback().into())
+    }
+}
+
+pub fn read(path: &str) -> AppResult<Vec<u8>> {
+    if path.is_empty() {
+        return Err("No path".into());
+    }
+    if path == "-" { return Err(AppError::NotFound(path.to_string())); }
+    if !path.is_ascii() { return Err(format!("Not ASCII: {}", path).into()); }
+    let bytes = std::fs::read(path).chain_err(|| AppErrorKind::NotFound(path.to_string()))?;
+    if bytes.is_empty() {
+        return Err(AppError::Empty);
+    }
+    Ok(bytes)
+}
+
+pub fn first_line(path: &str) -> AppResult<String> {
+    let text = String::from_utf8(read(path)?).chain_err(|| "Not UTF-8")?;
+    text.lines().next().map(str::to_string).chain_err(|| AppErrorKind::Empty)
+}
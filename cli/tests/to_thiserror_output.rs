@@ -0,0 +1,42 @@
+//! Compiles the migration of `fixtures/to_thiserror_input.rs` into thiserror, which the
+//! tests of the `to_thiserror` module check against the output of `migrate_source`
+
+#[path = "fixtures/to_thiserror_output.rs"]
+mod output;
+
+use std::{env, fs};
+use std::error::Error;
+use output::{first_line, read, AppError, AppResultExt};
+
+#[test]
+fn migrated_call_sites() {
+    assert_eq!(read("").unwrap_err().to_string(), "No path");
+    assert!(matches!(read("-"), Err(AppError::NotFound(ref path)) if path == "-"));
+    assert_eq!(read("é").unwrap_err().to_string(), "Not ASCII: é");
+
+    let err = read("missing-file").unwrap_err();
+    assert_eq!(err.to_string(), "Not found: missing-file");
+    assert!(err.source().unwrap().downcast_ref::<std::io::Error>().is_some());
+}
+
+#[test]
+fn migrated_chaining() {
+    let dir = env::temp_dir().join(format!("error-chain-utils-to-thiserror-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let (text, binary, empty) = (dir.join("text"), dir.join("binary"), dir.join("empty"));
+    fs::write(&text, "first\nsecond\n").unwrap();
+    fs::write(&binary, [0xff]).unwrap();
+    fs::write(&empty, "").unwrap();
+    let results = [&text, &binary, &empty].map(|path| first_line(path.to_str().unwrap()));
+    fs::remove_dir_all(&dir).unwrap();
+
+    let [text, binary, empty] = results;
+    assert_eq!(text.unwrap(), "first");
+    let err = binary.unwrap_err();
+    assert!(matches!(err, AppError::Chained(ref kind, _) if matches!(**kind, AppError::Msg(_))));
+    assert_eq!(err.to_string(), "Not UTF-8");
+    assert!(err.source().unwrap().downcast_ref::<std::string::FromUtf8Error>().is_some());
+    assert!(matches!(empty, Err(AppError::Empty)));
+
+    assert!(matches!(None::<u8>.chain_err(|| AppError::Empty), Err(AppError::Empty)));
+}
@@ -0,0 +1,212 @@
+//! Expansions of the parsed definitions for error libraries other than `error-chain`
+
+pub(crate) mod snafu;
+pub(crate) mod standard;
+pub(crate) mod thiserror;
+
+use proc_macro2::{Ident, Span, TokenStream, TokenTree};
+use syn::{Attribute, Expr, Path, Type};
+use quote::quote;
+use crate::quick::{ErrorsChildElementEnum, RootElementEnum, RootElementVec};
+use crate::quick::errors_child_element::NormalError;
+
+/// Error library the definitions are expanded for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// An `error_chain!` invocation, the default
+    ErrorChain,
+    /// An enum deriving `thiserror::Error`
+    Thiserror,
+    /// Hand-written `ErrorKind` and `Error` types, without any dependency
+    Std,
+    /// An enum deriving `snafu::Snafu`, with context selectors
+    Snafu
+}
+
+/// Names of all the supported backends, as given to the `backend` option
+pub(crate) const BACKENDS: [&str; 4] = ["error_chain", "thiserror", "std", "snafu"];
+
+impl Backend {
+    pub fn from_name(name: &str) -> Option<Backend> {
+        match name {
+            "error_chain" => Some(Backend::ErrorChain),
+            "thiserror" => Some(Backend::Thiserror),
+            "std" => Some(Backend::Std),
+            "snafu" => Some(Backend::Snafu),
+            _ => None
+        }
+    }
+
+    /// Name of the backend, as given to the `backend` option
+    pub fn name(self) -> &'static str {
+        match self {
+            Backend::ErrorChain => "error_chain",
+            Backend::Thiserror => "thiserror",
+            Backend::Std => "std",
+            Backend::Snafu => "snafu"
+        }
+    }
+}
+
+/// A `links` entry
+pub(crate) struct LinkDef {
+    pub attrs: Vec<Attribute>,
+    pub variant: Ident,
+    pub error_path: Path
+}
+
+/// A `foreign_links` entry
+pub(crate) struct ForeignLinkDef {
+    pub attrs: Vec<Attribute>,
+    pub variant: Ident,
+    pub error_path: Path
+}
+
+/// A single error, with its arguments and messages parsed out of the `error_chain!` syntax
+pub(crate) struct ErrorDef {
+    pub attrs: Vec<Attribute>,
+    pub ident: Ident,
+    pub fields: Vec<(Ident, Type)>,
+    pub description: Option<Expr>,
+    /// Format string and arguments given to `display(...)`
+    pub display: Option<Vec<Expr>>
+}
+
+impl ErrorDef {
+    fn new(error: &NormalError) -> syn::Result<ErrorDef> {
+        let fields = error.args()
+            .map(|args| args.iter().map(|arg| (arg.ident.clone(), arg.ty.clone())).collect())
+            .unwrap_or_default();
+        let display = match error.display() {
+            Some(val) if val.chained.is_some() => {
+                return Err(syn::Error::new(val.ident.span(), "The 'display(..) -> (..)' form is only supported by the error_chain backend"));
+            },
+            Some(val) => Some(val.args.iter().cloned().collect()),
+            None => None
+        };
+        Ok(ErrorDef {
+            attrs: error.attrs().to_vec(),
+            ident: error.ident().clone(),
+            fields,
+            description: error.description().map(|val| val.expr.clone()),
+            display
+        })
+    }
+
+    /// Replaces the field names used in `tokens` by `replace(index)`, as
+    /// backends other than `error_chain!` can't bind the fields to plain names
+    pub fn map_fields(&self, tokens: TokenStream, replace: &dyn Fn(usize, &Ident) -> TokenStream) -> TokenStream {
+        let mut output = TokenStream::new();
+        let mut after_dot = false;
+        for tree in tokens {
+            let is_dot = matches!(tree, TokenTree::Punct(ref val) if val.as_char() == '.' || val.as_char() == ':');
+            match tree {
+                TokenTree::Ident(ref ident) if !after_dot => {
+                    match self.fields.iter().position(|(field, _)| field == ident) {
+                        Some(index) => output.extend(replace(index, ident)),
+                        None => output.extend(Some(tree.clone()))
+                    }
+                },
+                TokenTree::Group(ref group) => {
+                    let mut new_group = proc_macro2::Group::new(group.delimiter(), self.map_fields(group.stream(), replace));
+                    new_group.set_span(group.span());
+                    output.extend(Some(TokenTree::from(new_group)));
+                },
+                _ => output.extend(Some(tree.clone()))
+            }
+            after_dot = is_dot;
+        }
+        output
+    }
+}
+
+/// Plain view of all the definitions of an invocation, with defaults filled in
+pub(crate) struct Definitions {
+    pub error: Ident,
+    pub error_kind: Ident,
+    pub result_ext: Ident,
+    pub result: Option<Ident>,
+    pub links: Vec<LinkDef>,
+    pub foreign_links: Vec<ForeignLinkDef>,
+    pub errors: Vec<ErrorDef>,
+    pub msg_variant: bool,
+    /// Extra derives for the error type
+    pub derives: Vec<Path>
+}
+
+impl Definitions {
+    /// Collects the definitions, once all `quick!` errors have been converted
+    pub fn new(root: &RootElementVec) -> syn::Result<Definitions> {
+        let mut definitions = Definitions {
+            error: Ident::new("Error", Span::call_site()),
+            error_kind: Ident::new("ErrorKind", Span::call_site()),
+            result_ext: Ident::new("ResultExt", Span::call_site()),
+            result: Some(Ident::new("Result", Span::call_site())),
+            links: vec![],
+            foreign_links: vec![],
+            errors: vec![],
+            msg_variant: true,
+            derives: root.options().derives().to_vec()
+        };
+        for item in root.items() {
+            match item {
+                RootElementEnum::TypesIdGroup(val) => {
+                    if let Some(names) = val.names() {
+                        definitions.error = names.error.clone();
+                        definitions.error_kind = names.error_kind.clone();
+                        definitions.result_ext = names.result_ext.clone();
+                        definitions.result = names.result.clone();
+                    }
+                },
+                RootElementEnum::LinksIdGroup(val) => {
+                    for link in val.links() {
+                        definitions.links.push(LinkDef {
+                            attrs: link.attrs.clone(),
+                            variant: link.variant.clone(),
+                            error_path: link.error_path.clone()
+                        });
+                    }
+                },
+                RootElementEnum::ForeignLinksIdGroup(val) => {
+                    for link in val.links() {
+                        definitions.foreign_links.push(ForeignLinkDef {
+                            attrs: link.attrs.clone(),
+                            variant: link.variant.clone(),
+                            error_path: link.error_path.clone()
+                        });
+                    }
+                },
+                RootElementEnum::ErrorsIdGroup(val) => {
+                    for error in val.items() {
+                        match error {
+                            ErrorsChildElementEnum::NormalError(val) => definitions.errors.push(ErrorDef::new(val)?),
+                            ErrorsChildElementEnum::QuickError(_) => panic!("Not all QuickError structs were converted to NormalError ones")
+                        }
+                    }
+                },
+                RootElementEnum::SkipMsgVariant(_) => definitions.msg_variant = false
+            }
+        }
+        Ok(definitions)
+    }
+
+    /// `Result` alias, when the `types` section asks for one
+    pub fn result_alias(&self) -> TokenStream {
+        match self.result {
+            Some(ref result) => {
+                let error = &self.error;
+                quote!{
+                    /// Convenient wrapper around `std::Result`.
+                    #[allow(unused)]
+                    pub type #result<T> = ::std::result::Result<T, #error>;
+                }
+            },
+            None => TokenStream::new()
+        }
+    }
+}
+
+/// Tuple field index as a token, for `.0` style accessors
+pub(crate) fn index(index: usize) -> syn::Index {
+    syn::Index::from(index)
+}
@@ -0,0 +1,84 @@
+//! Expansion into an enum deriving `snafu::Snafu`
+
+use proc_macro2::TokenStream;
+use syn::{LitStr, Path};
+use quote::{quote, ToTokens};
+use crate::backend::{Definitions, ErrorDef};
+
+/// Arguments of the `display(...)` given to `#[snafu(...)]`, which sees the fields by name
+fn display_args(error: &ErrorDef) -> TokenStream {
+    match (&error.display, &error.description) {
+        (Some(display), _) => quote!(#(#display),*),
+        (None, Some(description)) => quote!("{}", #description),
+        (None, None) => LitStr::new(&error.ident.to_string(), error.ident.span()).to_token_stream()
+    }
+}
+
+/// Expands the definitions into a single error enum with context selectors,
+/// with `ErrorKind` as an alias of it
+pub(crate) fn expand(crate_path: &Path, defs: &Definitions) -> syn::Result<TokenStream> {
+    let mut variants = TokenStream::new();
+    let links = defs.links.iter().map(|link| (&link.attrs, &link.variant, &link.error_path))
+        .chain(defs.foreign_links.iter().map(|link| (&link.attrs, &link.variant, &link.error_path)));
+    for (attrs, variant, error_path) in links {
+        variants.extend(quote!{
+            #(#attrs)*
+            #[snafu(context(false), display("{}", source))]
+            #variant { source: #error_path },
+        });
+    }
+    for error in &defs.errors {
+        let attrs = &error.attrs;
+        let ident = &error.ident;
+        let args = display_args(error);
+        let fields = error.fields.iter().map(|(name, ty)| quote!(#name: #ty));
+        variants.extend(quote!{
+            #(#attrs)*
+            #[snafu(display(#args))]
+            #ident { #(#fields),* },
+        });
+    }
+
+    let error = &defs.error;
+    let error_kind = &defs.error_kind;
+    let mut msg_impls = TokenStream::new();
+    if defs.msg_variant {
+        variants.extend(quote!{
+            /// A convenient variant for String.
+            #[snafu(display("{}", message))]
+            Msg { message: String },
+        });
+        msg_impls.extend(quote!{
+            impl<'a> ::std::convert::From<&'a str> for #error {
+                fn from(s: &'a str) -> Self {
+                    #error::Msg { message: s.into() }
+                }
+            }
+
+            impl ::std::convert::From<String> for #error {
+                fn from(s: String) -> Self {
+                    #error::Msg { message: s }
+                }
+            }
+        });
+    }
+    let result = defs.result_alias();
+    let derives = &defs.derives;
+
+    Ok(quote!{
+        /// The Error type.
+        #[derive(Debug, #crate_path::Snafu #(, #derives)*)]
+        #[snafu(crate_root(#crate_path), visibility(pub))]
+        pub enum #error {
+            #variants
+        }
+
+        #msg_impls
+
+        /// Alias of the error type, for code written against `error_chain!` kinds.
+        #[allow(unused)]
+        pub type #error_kind = #error;
+
+        #result
+    })
+}
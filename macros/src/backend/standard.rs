@@ -0,0 +1,239 @@
+//! Expansion into plain `ErrorKind` and `Error` types, using nothing but the standard library
+
+use proc_macro2::TokenStream;
+use syn::{LitStr, Path};
+use quote::{quote, ToTokens};
+use crate::backend::{Definitions, ErrorDef};
+
+/// Match pattern of an error, binding its fields to their own names
+fn pattern(error_kind: &proc_macro2::Ident, error: &ErrorDef) -> TokenStream {
+    let ident = &error.ident;
+    if error.fields.is_empty() {
+        quote!(#error_kind::#ident)
+    } else {
+        let names = error.fields.iter().map(|(name, _)| name);
+        quote!(#error_kind::#ident(#(ref #names),*))
+    }
+}
+
+/// Arguments of the `write!` call displaying an error
+fn display_args(error: &ErrorDef) -> TokenStream {
+    match (&error.display, &error.description) {
+        (Some(display), _) => quote!(#(#display),*),
+        (None, Some(description)) => quote!("{}", #description),
+        (None, None) => LitStr::new(&error.ident.to_string(), error.ident.span()).to_token_stream()
+    }
+}
+
+/// Expands the definitions into an `ErrorKind` enum, an `Error` struct wrapping it
+/// along with an optional source, and a `ResultExt` trait providing `chain_err`
+pub(crate) fn expand(crate_path: &Path, defs: &Definitions) -> syn::Result<TokenStream> {
+    let error = &defs.error;
+    let error_kind = &defs.error_kind;
+    let result_ext = &defs.result_ext;
+
+    let mut variants = TokenStream::new();
+    let mut display_arms = TokenStream::new();
+    let mut source_arms = TokenStream::new();
+    let mut from_impls = TokenStream::new();
+    let links = defs.links.iter().map(|link| (&link.attrs, &link.variant, &link.error_path))
+        .chain(defs.foreign_links.iter().map(|link| (&link.attrs, &link.variant, &link.error_path)));
+    for (attrs, variant, error_path) in links {
+        let cfgs: Vec<_> = attrs.iter().filter(|attr| attr.path.is_ident("cfg")).collect();
+        variants.extend(quote!{
+            #(#attrs)*
+            #variant(#error_path),
+        });
+        display_arms.extend(quote!{
+            #(#cfgs)*
+            #error_kind::#variant(ref err) => #crate_path::fmt::Display::fmt(err, fmt),
+        });
+        source_arms.extend(quote!{
+            #(#cfgs)*
+            #error_kind::#variant(ref err) => Some(err),
+        });
+        from_impls.extend(quote!{
+            #(#cfgs)*
+            impl #crate_path::convert::From<#error_path> for #error {
+                fn from(err: #error_path) -> Self {
+                    #error::from_kind(#error_kind::#variant(err))
+                }
+            }
+        });
+    }
+    for def in &defs.errors {
+        let attrs = &def.attrs;
+        let cfgs = attrs.iter().filter(|attr| attr.path.is_ident("cfg"));
+        let ident = &def.ident;
+        let fields = if def.fields.is_empty() {
+            TokenStream::new()
+        } else {
+            let types = def.fields.iter().map(|(_, ty)| ty);
+            quote!((#(#types),*))
+        };
+        let pattern = pattern(error_kind, def);
+        let args = display_args(def);
+        variants.extend(quote!{
+            #(#attrs)*
+            #ident #fields,
+        });
+        display_arms.extend(quote!{
+            #(#cfgs)*
+            #pattern => write!(fmt, #args),
+        });
+    }
+    if defs.msg_variant {
+        variants.extend(quote!{
+            /// A convenient variant for String.
+            Msg(String),
+        });
+        display_arms.extend(quote!{
+            #error_kind::Msg(ref s) => fmt.write_str(s),
+        });
+        from_impls.extend(quote!{
+            impl<'a> #crate_path::convert::From<&'a str> for #error_kind {
+                fn from(s: &'a str) -> Self {
+                    #error_kind::Msg(s.into())
+                }
+            }
+
+            impl #crate_path::convert::From<String> for #error_kind {
+                fn from(s: String) -> Self {
+                    #error_kind::Msg(s)
+                }
+            }
+
+            impl<'a> #crate_path::convert::From<&'a str> for #error {
+                fn from(s: &'a str) -> Self {
+                    #error::from_kind(s.into())
+                }
+            }
+
+            impl #crate_path::convert::From<String> for #error {
+                fn from(s: String) -> Self {
+                    #error::from_kind(s.into())
+                }
+            }
+        });
+    }
+    let result = defs.result_alias();
+    let derives = &defs.derives;
+
+    Ok(quote!{
+        /// The kind of an error.
+        #[derive(Debug #(, #derives)*)]
+        pub enum #error_kind {
+            #variants
+        }
+
+        impl #crate_path::fmt::Display for #error_kind {
+            #[allow(unused_variables)]
+            fn fmt(&self, fmt: &mut #crate_path::fmt::Formatter) -> #crate_path::fmt::Result {
+                match *self {
+                    #display_arms
+                }
+            }
+        }
+
+        /// The Error type.
+        #[derive(Debug)]
+        pub struct #error {
+            kind: #error_kind,
+            source: Option<Box<dyn #crate_path::error::Error + Send + 'static>>
+        }
+
+        #[allow(unused)]
+        impl #error {
+            /// Constructs an error from a kind.
+            pub fn from_kind(kind: #error_kind) -> Self {
+                #error { kind, source: None }
+            }
+
+            /// Constructs a chained error from another error and a kind.
+            pub fn with_chain<E, K>(error: E, kind: K) -> Self
+                where E: #crate_path::error::Error + Send + 'static,
+                      K: Into<#error_kind>
+            {
+                #error { kind: kind.into(), source: Some(Box::new(error)) }
+            }
+
+            /// Returns the kind of the error.
+            pub fn kind(&self) -> &#error_kind {
+                &self.kind
+            }
+
+            /// Consumes the error, returning its kind.
+            pub fn into_kind(self) -> #error_kind {
+                self.kind
+            }
+
+            /// Chains this error with another kind.
+            pub fn chain_err<F, EK>(self, error: F) -> Self
+                where F: FnOnce() -> EK,
+                      EK: Into<#error_kind>
+            {
+                #error::with_chain(self, error())
+            }
+        }
+
+        impl #crate_path::fmt::Display for #error {
+            fn fmt(&self, fmt: &mut #crate_path::fmt::Formatter) -> #crate_path::fmt::Result {
+                #crate_path::fmt::Display::fmt(&self.kind, fmt)
+            }
+        }
+
+        impl #crate_path::error::Error for #error {
+            #[allow(unreachable_patterns)]
+            fn source(&self) -> Option<&(dyn #crate_path::error::Error + 'static)> {
+                if let Some(ref source) = self.source {
+                    return Some(&**source);
+                }
+                match self.kind {
+                    #source_arms
+                    _ => None
+                }
+            }
+        }
+
+        impl #crate_path::convert::From<#error_kind> for #error {
+            fn from(kind: #error_kind) -> Self {
+                #error::from_kind(kind)
+            }
+        }
+
+        #from_impls
+
+        /// Additional methods for `Result` and `Option`, for easy interaction with this crate.
+        pub trait #result_ext<T> {
+            /// If the `Result` is an `Err` then `chain_err` evaluates the closure,
+            /// which returns *some type that can be converted to `ErrorKind`*, boxes
+            /// the original error to store as the cause, then returns a new error
+            /// containing the original error.
+            fn chain_err<F, EK>(self, callback: F) -> #crate_path::result::Result<T, #error>
+                where F: FnOnce() -> EK,
+                      EK: Into<#error_kind>;
+        }
+
+        impl<T, E> #result_ext<T> for #crate_path::result::Result<T, E>
+            where E: #crate_path::error::Error + Send + 'static
+        {
+            fn chain_err<F, EK>(self, callback: F) -> #crate_path::result::Result<T, #error>
+                where F: FnOnce() -> EK,
+                      EK: Into<#error_kind>
+            {
+                self.map_err(move |e| #error::with_chain(e, callback()))
+            }
+        }
+
+        impl<T> #result_ext<T> for Option<T> {
+            fn chain_err<F, EK>(self, callback: F) -> #crate_path::result::Result<T, #error>
+                where F: FnOnce() -> EK,
+                      EK: Into<#error_kind>
+            {
+                self.ok_or_else(move || #error::from_kind(callback().into()))
+            }
+        }
+
+        #result
+    })
+}
@@ -0,0 +1,115 @@
+//! Expansion into an enum deriving `thiserror::Error`
+
+use proc_macro2::TokenStream;
+use syn::{LitStr, Path};
+use quote::{quote, ToTokens};
+use crate::backend::{Definitions, ErrorDef, index};
+
+/// Arguments of the `#[error(...)]` attribute of an error, with the field names
+/// rewritten to thiserror's `.0` style accessors
+fn error_attr_args(error: &ErrorDef) -> TokenStream {
+    let to_accessor = |position: usize, _: &proc_macro2::Ident| {
+        let position = index(position);
+        quote!(.#position)
+    };
+    match (&error.display, &error.description) {
+        (Some(display), _) => {
+            let mut args = TokenStream::new();
+            for (position, arg) in display.iter().enumerate() {
+                if position > 0 {
+                    args.extend(quote!(,));
+                }
+                args.extend(error.map_fields(arg.to_token_stream(), &to_accessor));
+            }
+            args
+        },
+        (None, Some(description)) => {
+            let description = error.map_fields(description.to_token_stream(), &to_accessor);
+            quote!("{}", #description)
+        },
+        (None, None) => LitStr::new(&error.ident.to_string(), error.ident.span()).to_token_stream()
+    }
+}
+
+/// Expands the definitions into a single error enum, with `ErrorKind` as an alias of it
+pub(crate) fn expand(crate_path: &Path, defs: &Definitions) -> syn::Result<TokenStream> {
+    let mut variants = TokenStream::new();
+    for link in &defs.links {
+        let attrs = &link.attrs;
+        let variant = &link.variant;
+        let error_path = &link.error_path;
+        variants.extend(quote!{
+            #(#attrs)*
+            #[error(transparent)]
+            #variant(#[from] #error_path),
+        });
+    }
+    for link in &defs.foreign_links {
+        let attrs = &link.attrs;
+        let variant = &link.variant;
+        let error_path = &link.error_path;
+        variants.extend(quote!{
+            #(#attrs)*
+            #[error(transparent)]
+            #variant(#[from] #error_path),
+        });
+    }
+    for error in &defs.errors {
+        let attrs = &error.attrs;
+        let ident = &error.ident;
+        let args = error_attr_args(error);
+        let fields = if error.fields.is_empty() {
+            TokenStream::new()
+        } else {
+            let types = error.fields.iter().map(|(_, ty)| ty);
+            quote!((#(#types),*))
+        };
+        variants.extend(quote!{
+            #(#attrs)*
+            #[error(#args)]
+            #ident #fields,
+        });
+    }
+
+    let error = &defs.error;
+    let error_kind = &defs.error_kind;
+    let mut msg_impls = TokenStream::new();
+    if defs.msg_variant {
+        variants.extend(quote!{
+            /// A convenient variant for String.
+            #[error("{0}")]
+            Msg(String),
+        });
+        msg_impls.extend(quote!{
+            impl<'a> ::std::convert::From<&'a str> for #error {
+                fn from(s: &'a str) -> Self {
+                    #error::Msg(s.into())
+                }
+            }
+
+            impl ::std::convert::From<String> for #error {
+                fn from(s: String) -> Self {
+                    #error::Msg(s)
+                }
+            }
+        });
+    }
+    let result = defs.result_alias();
+    let derives = &defs.derives;
+
+    Ok(quote!{
+        /// The Error type.
+        #[derive(Debug, #crate_path::Error #(, #derives)*)]
+        pub enum #error {
+            #variants
+        }
+
+        #msg_impls
+
+        /// Alias of the error type, for code written against `error_chain!` kinds.
+        #[allow(unused)]
+        pub type #error_kind = #error;
+
+        #result
+    })
+}
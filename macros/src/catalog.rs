@@ -0,0 +1,322 @@
+//! Metadata of the errors of an invocation, and the `ERROR_CATALOG` static listing them
+//!
+//! Errors can be annotated with `#[catalog(code = "E0001", severity = "error", help = "...")]`,
+//! every key being optional. The annotations are always removed before the expansion,
+//! and only end up in the generated code when the `catalog` option is set.
+
+use proc_macro2::TokenStream;
+use syn::{Attribute, Expr, ExprLit, Ident, Lit, LitStr, Path, Token, Type, parse::{Parse, ParseStream}, punctuated::Punctuated};
+use quote::{quote, ToTokens};
+use crate::fold::{self, Fold, Visit};
+use crate::options::Options;
+use crate::quick::{ErrorsChildElementEnum, RootElementVec};
+use crate::quick::errors_child_element::{NormalError, QuickError};
+use crate::suggest;
+
+/// Keys accepted by the `#[catalog(...)]` attribute
+const KEYS: [&str; 3] = ["code", "severity", "help"];
+
+/// Whether an attribute is a `#[catalog(...)]` annotation
+fn is_catalog(attr: &Attribute) -> bool {
+    attr.path.is_ident("catalog")
+}
+
+/// A single `key = "value"` of a `#[catalog(...)]` attribute
+struct MetadataItem {
+    key: Ident,
+    value: LitStr
+}
+
+impl Parse for MetadataItem {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let key = input.parse::<Ident>()?;
+        let name = key.to_string();
+        if !KEYS.contains(&name.as_str()) {
+            let message = match suggest::closest(&name, &KEYS) {
+                Some(val) => format!("Unknown catalog key '{}', did you mean '{}'?", name, val),
+                None => format!("Unknown catalog key '{}', expected one of {}", name, KEYS.join(", "))
+            };
+            return Err(syn::Error::new(key.span(), message));
+        }
+        input.parse::<Token![=]>()?;
+        Ok(MetadataItem { key, value: input.parse()? })
+    }
+}
+
+/// Metadata given to an error through `#[catalog(...)]` attributes
+#[derive(Debug, Clone, Default)]
+pub struct Metadata {
+    pub code: Option<LitStr>,
+    pub severity: Option<LitStr>,
+    pub help: Option<LitStr>
+}
+
+impl Metadata {
+    /// Collects the metadata of the `#[catalog(...)]` attributes among `attrs`, ignoring the others
+    pub fn from_attrs(attrs: &[Attribute]) -> syn::Result<Metadata> {
+        let mut metadata = Metadata::default();
+        for attr in attrs.iter().filter(|attr| is_catalog(attr)) {
+            for item in attr.parse_args_with(Punctuated::<MetadataItem, Token![,]>::parse_terminated)? {
+                let slot = match item.key.to_string().as_str() {
+                    "code" => &mut metadata.code,
+                    "severity" => &mut metadata.severity,
+                    _ => &mut metadata.help
+                };
+                if slot.is_some() {
+                    return Err(syn::Error::new(item.key.span(), format!("The '{}' catalog key was already given", item.key)));
+                }
+                *slot = Some(item.value);
+            }
+        }
+        Ok(metadata)
+    }
+}
+
+/// An error as listed in the catalog
+#[derive(Debug, Clone)]
+pub struct ErrorEntry {
+    pub ident: Ident,
+    pub description: String,
+    /// Format string of the display, the description when the error has none
+    pub display: String,
+    pub args: Vec<(Ident, Type)>,
+    pub metadata: Metadata
+}
+
+/// Value of a string literal, or the tokens of any other expression
+fn message(expr: &Expr) -> String {
+    match expr {
+        Expr::Lit(ExprLit { lit: Lit::Str(ref val), .. }) => val.value(),
+        other => other.to_token_stream().to_string()
+    }
+}
+
+impl ErrorEntry {
+    fn new(error: &NormalError) -> syn::Result<ErrorEntry> {
+        let description = error.description().map(|val| message(&val.expr)).unwrap_or_default();
+        let display = match error.display() {
+            // The format string of `display(self_) -> ("...", args)` comes after the arrow
+            Some(val) => match val.chained {
+                Some((_, _, ref args)) => args.first().map(message),
+                None => val.args.first().map(message)
+            },
+            None => None
+        };
+        Ok(ErrorEntry {
+            ident: error.ident().clone(),
+            display: display.unwrap_or_else(|| description.clone()),
+            description,
+            args: error.args()
+                .map(|args| args.iter().map(|arg| (arg.ident.clone(), arg.ty.clone())).collect())
+                .unwrap_or_default(),
+            metadata: Metadata::from_attrs(error.attrs())?
+        })
+    }
+}
+
+struct CollectEntries<'a> {
+    options: &'a Options,
+    entries: Vec<ErrorEntry>,
+    error: Option<syn::Error>
+}
+
+impl CollectEntries<'_> {
+    fn push(&mut self, entry: syn::Result<ErrorEntry>) {
+        match entry {
+            Ok(val) => self.entries.push(val),
+            Err(e) => match self.error {
+                Some(ref mut error) => error.combine(e),
+                None => self.error = Some(e)
+            }
+        }
+    }
+}
+
+impl Visit for CollectEntries<'_> {
+    fn visit_quick_error(&mut self, error: &QuickError) {
+        let entry = ErrorEntry::new(&error.clone().into_normal_with(self.options));
+        self.push(entry);
+    }
+
+    fn visit_normal_error(&mut self, error: &NormalError) {
+        self.push(ErrorEntry::new(error));
+    }
+}
+
+/// Lists the errors of the `errors` section, `quick!` entries included, in order
+///
+/// Fails on malformed `#[catalog(...)]` attributes.
+pub fn entries(root: &RootElementVec) -> syn::Result<Vec<ErrorEntry>> {
+    let mut collector = CollectEntries { options: root.options(), entries: vec![], error: None };
+    collector.visit_root(root);
+    match collector.error {
+        Some(e) => Err(e),
+        None => Ok(collector.entries)
+    }
+}
+
+/// Pass removing the `#[catalog(...)]` attributes, which the backends know nothing about
+pub(crate) struct StripMetadata;
+
+impl Fold for StripMetadata {
+    fn fold_quick_error(&mut self, mut error: QuickError) -> syn::Result<ErrorsChildElementEnum> {
+        error.attrs_mut().retain(|attr| !is_catalog(attr));
+        Ok(ErrorsChildElementEnum::QuickError(error))
+    }
+
+    fn fold_normal_error(&mut self, mut error: NormalError) -> syn::Result<NormalError> {
+        error.attrs_mut().retain(|attr| !is_catalog(attr));
+        fold::fold_normal_error(self, error)
+    }
+}
+
+fn option_tokens(value: &Option<LitStr>) -> TokenStream {
+    match value {
+        Some(ref val) => quote!(::std::option::Option::Some(#val)),
+        None => quote!(::std::option::Option::None)
+    }
+}
+
+/// The `ERROR_CATALOG` static listing `entries`, made of the types of the `catalog` module at `path`
+pub(crate) fn expand(entries: &[ErrorEntry], path: &Path) -> TokenStream {
+    let infos = entries.iter().map(|entry| {
+        let name = entry.ident.to_string();
+        let (description, display) = (&entry.description, &entry.display);
+        let args = entry.args.iter().map(|(ident, ty)| {
+            let name = ident.to_string();
+            quote!(#path::ArgInfo { name: #name, ty: stringify!(#ty) })
+        });
+        let code = option_tokens(&entry.metadata.code);
+        let severity = option_tokens(&entry.metadata.severity);
+        let help = option_tokens(&entry.metadata.help);
+        quote!{
+            #path::ErrorInfo {
+                name: #name,
+                description: #description,
+                display: #display,
+                args: &[#(#args),*],
+                code: #code,
+                severity: #severity,
+                help: #help
+            }
+        }
+    });
+    quote!{
+        /// Every error of the `errors` section, with its messages and metadata
+        #[allow(unused)]
+        pub static ERROR_CATALOG: &[#path::ErrorInfo] = &[#(#infos),*];
+    }
+}
+
+
+#[cfg(test)]
+mod tests{
+    use std::assert_eq;
+    use quote::quote;
+    use crate::catalog::entries;
+    use crate::quick;
+
+    #[test]
+    pub fn test_entries() {
+        let parsed = quick::parse(quote!{
+            errors {
+                #[catalog(code = "E0001", severity = "error")]
+                quick!(NotFound, "Not found", (path,))
+                #[catalog(help = "Check the code")]
+                Custom(code: u32) {
+                    description("Custom")
+                    display("Custom error: {}", code)
+                }
+                Plain {
+                    description("Plain")
+                }
+            }
+        }).unwrap();
+        let entries = entries(&parsed).unwrap();
+        let names: Vec<String> = entries.iter().map(|entry| entry.ident.to_string()).collect();
+        assert_eq!(names, ["NotFound", "Custom", "Plain"]);
+        assert_eq!(entries[0].display, "Not found: {}");
+        assert_eq!(entries[0].args[0].0.to_string(), "path");
+        assert_eq!(entries[0].metadata.code.as_ref().unwrap().value(), "E0001");
+        assert_eq!(entries[0].metadata.severity.as_ref().unwrap().value(), "error");
+        assert!(entries[0].metadata.help.is_none());
+        assert_eq!(entries[1].display, "Custom error: {}");
+        assert_eq!(entries[1].metadata.help.as_ref().unwrap().value(), "Check the code");
+        assert_eq!(entries[2].display, "Plain");
+    }
+
+    #[test]
+    pub fn test_metadata_errors() {
+        let parsed = quick::parse(quote!{
+            errors {
+                #[catalog(cdoe = "E0001")]
+                quick!(NotFound, "Not found")
+            }
+        }).unwrap();
+        assert_eq!(entries(&parsed).unwrap_err().to_string(), "Unknown catalog key 'cdoe', did you mean 'code'?");
+
+        let parsed = quick::parse(quote!{
+            errors {
+                #[catalog(code = "E0001")]
+                #[catalog(code = "E0002")]
+                quick!(NotFound, "Not found")
+            }
+        }).unwrap();
+        assert_eq!(entries(&parsed).unwrap_err().to_string(), "The 'code' catalog key was already given");
+    }
+
+    #[test]
+    pub fn test_expand() {
+        let output = quick::main(quote!{
+            catalog = true;
+            errors {
+                #[catalog(code = "E0001")]
+                quick!(NotFound, "Not found", (path,))
+            }
+        }).unwrap();
+        let expected_output = quote!{
+            ::error_chain_utils::error_chain::error_chain!{
+                errors {
+                    NotFound (path: String) {
+                        description("Not found")
+                        display("Not found: {}", path)
+                    }
+                }
+            }
+            /// Every error of the `errors` section, with its messages and metadata
+            #[allow(unused)]
+            pub static ERROR_CATALOG: &[::error_chain_utils::catalog::ErrorInfo] = &[
+                ::error_chain_utils::catalog::ErrorInfo {
+                    name: "NotFound",
+                    description: "Not found",
+                    display: "Not found: {}",
+                    args: &[::error_chain_utils::catalog::ArgInfo { name: "path", ty: stringify!(String) }],
+                    code: ::std::option::Option::Some("E0001"),
+                    severity: ::std::option::Option::None,
+                    help: ::std::option::Option::None
+                }
+            ];
+        };
+        assert_eq!(output.to_string(), expected_output.to_string());
+
+        let output = quick::main(quote!{
+            backend = std;
+            catalog = true;
+            catalog_path = ::my_utils::catalog;
+            errors {
+                quick!(NotFound, "Not found")
+            }
+        }).unwrap().to_string();
+        assert!(output.contains(&quote!(pub static ERROR_CATALOG: &[::my_utils::catalog::ErrorInfo]).to_string()));
+        assert!(!output.contains("error_chain_utils"));
+
+        // The metadata is dropped when no catalog is asked for
+        let output = quick::main(quote!{
+            errors {
+                #[catalog(code = "E0001")]
+                quick!(NotFound, "Not found")
+            }
+        }).unwrap();
+        assert!(!output.to_string().contains("catalog"));
+    }
+}
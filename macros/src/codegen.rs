@@ -0,0 +1,194 @@
+//! Generation of error modules from definition files, for build scripts
+//!
+//! Unlike `error_chain_from_file!`, the generated code is written to a file of its own,
+//! so that IDEs can see it and changes to it show up in diffs. Besides the definitions,
+//! the file holds an impl of the error kind type, with the `code()`, `severity()` and
+//! `help()` of each error as given in the definition file.
+//!
+//! ```no_run
+//! // In the `main` function of build.rs, the module then being declared with `mod errors;`
+//! println!("cargo:rerun-if-changed=errors.toml");
+//! error_chain_utils_lib::codegen::generate("errors.toml", "src/errors.rs").unwrap();
+//! ```
+
+use std::{fmt, fs, io};
+use std::path::{Path, PathBuf};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+use syn::LitStr;
+use crate::catalog::{self, ErrorEntry};
+use crate::from_file::{self, DefinitionError};
+use crate::quick::{RootElementEnum, RootElementVec};
+use crate::{pretty, quick};
+
+/// An error while generating an error module
+#[derive(Debug)]
+pub enum CodegenError {
+    /// The definition file could not be read or parsed
+    Definition(DefinitionError),
+    /// The definitions could not be expanded
+    Expansion(PathBuf, syn::Error),
+    /// The output file could not be written
+    Write(PathBuf, io::Error)
+}
+
+impl fmt::Display for CodegenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Definition(ref e) => e.fmt(f),
+            Self::Expansion(ref path, ref e) => write!(f, "{}: {}", path.display(), e),
+            Self::Write(ref path, ref e) => write!(f, "{}: {}", path.display(), e)
+        }
+    }
+}
+
+impl std::error::Error for CodegenError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Definition(ref e) => Some(e),
+            Self::Expansion(_, ref e) => Some(e),
+            Self::Write(_, ref e) => Some(e)
+        }
+    }
+}
+
+impl From<DefinitionError> for CodegenError {
+    fn from(e: DefinitionError) -> Self {
+        CodegenError::Definition(e)
+    }
+}
+
+/// Name of the error kind type, which every backend defines with a variant per error
+fn error_kind(parsed: &RootElementVec) -> Ident {
+    for item in parsed.items() {
+        if let RootElementEnum::TypesIdGroup(ref val) = item {
+            if let Some(names) = val.names() {
+                return names.error_kind.clone();
+            }
+        }
+    }
+    Ident::new("ErrorKind", Span::call_site())
+}
+
+/// Method returning one of the metadata of the errors, `None` for the errors without it
+fn metadata_method(name: &str, doc: &str, entries: &[ErrorEntry], value: &dyn Fn(&ErrorEntry) -> Option<&LitStr>) -> TokenStream {
+    let name = Ident::new(name, Span::call_site());
+    let arms: Vec<TokenStream> = entries.iter().filter_map(|entry| {
+        let ident = &entry.ident;
+        // `{ .. }` matches tuple, struct and unit variants alike
+        value(entry).map(|lit| quote!(Self::#ident { .. } => ::std::option::Option::Some(#lit),))
+    }).collect();
+    let body = if arms.is_empty() {
+        quote!(::std::option::Option::None)
+    } else {
+        quote!{
+            #[allow(unreachable_patterns)]
+            match self {
+                #(#arms)*
+                _ => ::std::option::Option::None
+            }
+        }
+    };
+    quote!{
+        #[doc = #doc]
+        pub fn #name(&self) -> ::std::option::Option<&'static str> {
+            #body
+        }
+    }
+}
+
+/// Impl of the error kind type giving the code, severity and help of each error
+fn helpers(parsed: &RootElementVec) -> syn::Result<TokenStream> {
+    let entries = catalog::entries(parsed)?;
+    let error_kind = error_kind(parsed);
+    let code = metadata_method("code", " Code of the error, as given in the definition file", &entries, &|entry| entry.metadata.code.as_ref());
+    let severity = metadata_method("severity", " Severity of the error, as given in the definition file", &entries, &|entry| entry.metadata.severity.as_ref());
+    let help = metadata_method("help", " Help text of the error, as given in the definition file", &entries, &|entry| entry.metadata.help.as_ref());
+    Ok(quote!{
+        #[allow(unused)]
+        impl #error_kind {
+            #code
+            #severity
+            #help
+        }
+    })
+}
+
+/// Returns the formatted code expanded from a definition file, headed by a comment naming it
+pub fn generate_string(input_path: &Path) -> Result<String, CodegenError> {
+    let expansion_error = |e| CodegenError::Expansion(input_path.to_path_buf(), e);
+    let parsed = from_file::load(input_path)?;
+    let helpers = pretty::items(helpers(&parsed).map_err(expansion_error)?).map_err(expansion_error)?;
+    let code = pretty::expansion(quick::expand(parsed).map_err(expansion_error)?).map_err(expansion_error)?;
+    let code = format!("{}\n\n{}", code, helpers.trim_end());
+    let name = input_path.file_name().map_or_else(|| input_path.display().to_string(), |val| val.to_string_lossy().into_owned());
+    Ok(format!("// Generated by error-chain-utils from {}, edit that file instead\n\n{}\n", name, code))
+}
+
+/// Writes the formatted code expanded from a definition file to `output_path`
+///
+/// The output is only written when it changed. Build scripts should also print
+/// `cargo:rerun-if-changed` for the definition file, which this function leaves to them.
+pub fn generate(input_path: impl AsRef<Path>, output_path: impl AsRef<Path>) -> Result<(), CodegenError> {
+    let (input_path, output_path) = (input_path.as_ref(), output_path.as_ref());
+    let code = generate_string(input_path)?;
+    if fs::read_to_string(output_path).map_or(true, |old| old != code) {
+        fs::write(output_path, code).map_err(|e| CodegenError::Write(output_path.to_path_buf(), e))?;
+    }
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests{
+    use std::{assert_eq, env, fs};
+    use crate::codegen::generate;
+
+    #[test]
+    pub fn test_generate() {
+        let dir = env::temp_dir().join(format!("error-chain-utils-codegen-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("errors.toml");
+        let output = dir.join("errors.rs");
+        fs::write(&input, "[[errors]]\nname = \"NotFound\"\ndescription = \"Not found\"\nargs = [\"path\"]\ncode = \"E0001\"\n").unwrap();
+        generate(&input, &output).unwrap();
+        let expected = r#"// Generated by error-chain-utils from errors.toml, edit that file instead
+
+::error_chain_utils::error_chain::error_chain! {
+    errors {
+        /// Error code: `E0001`
+        NotFound(path: String) {
+            description("Not found")
+            display("Not found: {}", path)
+        }
+    }
+}
+
+#[allow(unused)]
+impl ErrorKind {
+    /// Code of the error, as given in the definition file
+    pub fn code(&self) -> ::std::option::Option<&'static str> {
+        #[allow(unreachable_patterns)]
+        match self {
+            Self::NotFound { .. } => ::std::option::Option::Some("E0001"),
+            _ => ::std::option::Option::None,
+        }
+    }
+    /// Severity of the error, as given in the definition file
+    pub fn severity(&self) -> ::std::option::Option<&'static str> {
+        ::std::option::Option::None
+    }
+    /// Help text of the error, as given in the definition file
+    pub fn help(&self) -> ::std::option::Option<&'static str> {
+        ::std::option::Option::None
+    }
+}
+"#;
+        assert_eq!(fs::read_to_string(&output).unwrap(), expected);
+
+        fs::write(&input, "[[errors]]\nname = \"NotFound\"\n").unwrap();
+        let err = generate(&input, &output).unwrap_err();
+        assert!(err.to_string().starts_with(&format!("{}:1:1: missing field `description`", input.display())), "{}", err);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,67 @@
+//! Diagnostics reported for malformed `quick!` entries
+
+use std::fmt;
+use proc_macro2::Span;
+
+/// Reason why a `quick!` entry could not be parsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuickErrorReason {
+    /// `quick!` was not followed by a parenthesized argument list
+    MissingParens,
+    /// The first argument was not the name of the error variant
+    MissingIdent,
+    /// A comma was expected between two arguments
+    MissingComma,
+    /// The description was not a string literal
+    NonLiteralDescription,
+    /// The error arguments were not a parenthesized list of identifiers
+    BadArgumentList,
+    /// Tokens were left after the last argument
+    UnexpectedTokens
+}
+
+impl fmt::Display for QuickErrorReason {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(),fmt::Error> {
+        fmt.write_str(match self {
+            Self::MissingParens => "expected '(' after 'quick!'",
+            Self::MissingIdent => "expected the name of the error variant",
+            Self::MissingComma => "expected ','",
+            Self::NonLiteralDescription => "the description must be a string literal",
+            Self::BadArgumentList => "the arguments must be a parenthesized list of identifiers",
+            Self::UnexpectedTokens => "unexpected tokens after the last argument"
+        })
+    }
+}
+
+/// A malformed `quick!` entry, along with the span of the offending tokens
+#[derive(Debug, Clone)]
+pub struct QuickDiagnostic {
+    span: Span,
+    reason: QuickErrorReason
+}
+
+impl QuickDiagnostic {
+    pub fn new(span: Span, reason: QuickErrorReason) -> Self {
+        QuickDiagnostic { span, reason }
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    pub fn reason(&self) -> QuickErrorReason {
+        self.reason
+    }
+}
+
+impl fmt::Display for QuickDiagnostic {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(),fmt::Error> {
+        write!(fmt, "Invalid 'quick!()' macro: {}", self.reason)
+    }
+}
+
+impl From<QuickDiagnostic> for syn::Error {
+    fn from(diagnostic: QuickDiagnostic) -> syn::Error {
+        syn::Error::new(diagnostic.span, diagnostic.to_string())
+    }
+}
@@ -0,0 +1,167 @@
+//! Programmatic entry point to `error_chain_quick!`, for other proc macros and build scripts
+
+use proc_macro2::TokenStream;
+use syn::{Path, Type};
+use crate::fold::Fold;
+use crate::options::{Backend, DisplayStyle, Options};
+use crate::quick::{self, RootElementVec};
+
+/// Expands `error_chain_quick!` bodies with a set of defaults and extra passes
+///
+/// The defaults only apply where the invocation does not give the option itself.
+///
+/// ```
+/// use error_chain_utils_lib::expander::QuickExpander;
+/// use error_chain_utils_lib::options::{Backend, DisplayStyle};
+/// use quote::quote;
+///
+/// let mut expander = QuickExpander::new()
+///     .backend(Backend::Thiserror)
+///     .display_style(DisplayStyle::Parens)
+///     .derive(syn::parse_quote!(PartialEq));
+/// let output = expander.expand(quote!{
+///     errors {
+///         quick!(NotFound, "Not found", (path,))
+///     }
+/// }).unwrap();
+/// assert!(output.to_string().contains("\"Not found ({})\""));
+/// ```
+#[derive(Default)]
+pub struct QuickExpander {
+    options: Options,
+    passes: Vec<Box<dyn Fold>>
+}
+
+impl QuickExpander {
+    pub fn new() -> Self {
+        QuickExpander::default()
+    }
+
+    /// Path to the crate of the backend, see the `crate` option, only used
+    /// by the invocations that do not pick another backend
+    pub fn crate_path(mut self, path: Path) -> Self {
+        self.options.crate_path = Some(path);
+        self
+    }
+
+    pub fn backend(mut self, backend: Backend) -> Self {
+        self.options.backend = Some(backend);
+        self
+    }
+
+    /// Type of the arguments of `quick!` errors, instead of `String`
+    pub fn arg_type(mut self, ty: Type) -> Self {
+        self.options.arg_type = Some(ty);
+        self
+    }
+
+    pub fn display_style(mut self, style: DisplayStyle) -> Self {
+        self.options.display_style = Some(style);
+        self
+    }
+
+    /// Adds a derive to the generated error type, which the `error_chain` backend does not support
+    pub fn derive(mut self, path: Path) -> Self {
+        self.options.derives.push(path);
+        self
+    }
+
+    /// Emits the `ERROR_CATALOG` static next to the definitions, see the `catalog` option
+    pub fn catalog(mut self, catalog: bool) -> Self {
+        self.options.catalog = Some(catalog);
+        self
+    }
+
+    /// Path to the `catalog` module of `error-chain-utils` the catalog refers to, see the `catalog_path` option
+    pub fn catalog_path(mut self, path: Path) -> Self {
+        self.options.catalog_path = Some(path);
+        self
+    }
+
+    /// Adds a pass, run after the `quick!` errors are converted and after the passes added before it
+    pub fn pass<F: Fold + 'static>(mut self, pass: F) -> Self {
+        self.passes.push(Box::new(pass));
+        self
+    }
+
+    /// Parses and expands the body of an `error_chain_quick!` invocation
+    pub fn expand(&mut self, input: TokenStream) -> syn::Result<TokenStream> {
+        self.expand_parsed(quick::parse(input)?)
+    }
+
+    /// Expands already parsed definitions
+    pub fn expand_parsed(&mut self, mut parsed: RootElementVec) -> syn::Result<TokenStream> {
+        parsed.options_mut().merge_defaults(&self.options);
+        let mut passes: Vec<&mut dyn Fold> = self.passes.iter_mut().map(|pass| pass.as_mut() as &mut dyn Fold).collect();
+        quick::expand_with_passes(parsed, &mut passes)
+    }
+}
+
+
+#[cfg(test)]
+mod tests{
+    use std::assert_eq;
+    use quote::quote;
+    use syn::parse_quote;
+    use crate::expander::QuickExpander;
+    use crate::options::{Backend, DisplayStyle};
+
+    #[test]
+    pub fn test_defaults() {
+        let mut expander = QuickExpander::new()
+            .crate_path(parse_quote!(::my_error_chain))
+            .arg_type(parse_quote!(Box<str>))
+            .display_style(DisplayStyle::Named);
+        let output = expander.expand(quote!{
+            errors {
+                quick!(QuickError1, "Error 1 Description", (arg1, arg2))
+            }
+        }).unwrap();
+        let expected_output = quote!{
+            ::my_error_chain::error_chain!{
+                errors {
+                    QuickError1 (arg1: Box<str>, arg2: Box<str>) {
+                        description("Error 1 Description")
+                        display("Error 1 Description: arg1 = {}, arg2 = {}", arg1, arg2)
+                    }
+                }
+            }
+        };
+        assert_eq!(output.to_string(),expected_output.to_string());
+
+        // Options given in the invocation win over the defaults
+        let output = expander.expand(quote!{
+            crate = ::error_chain;
+            errors {
+                quick!(QuickError1, "Error 1 Description")
+            }
+        }).unwrap();
+        assert!(output.to_string().starts_with(&quote!(::error_chain::error_chain!).to_string()));
+
+        // The default crate path belongs to the default backend
+        let output = expander.expand(quote!{
+            backend = thiserror;
+            errors {
+                quick!(QuickError1, "Error 1 Description")
+            }
+        }).unwrap();
+        assert!(output.to_string().contains(&quote!(#[derive(Debug, ::thiserror::Error)]).to_string()));
+    }
+
+    #[test]
+    pub fn test_derives() {
+        let mut expander = QuickExpander::new()
+            .backend(Backend::Thiserror)
+            .derive(parse_quote!(PartialEq));
+        let output = expander.expand(quote!{
+            errors {
+                quick!(QuickError1, "Error 1 Description")
+            }
+        }).unwrap();
+        assert!(output.to_string().contains(&quote!(#[derive(Debug, ::thiserror::Error, PartialEq)]).to_string()));
+
+        let mut expander = QuickExpander::new().derive(parse_quote!(PartialEq));
+        let err = expander.expand(quote!{ errors { quick!(A, "A") } }).unwrap_err();
+        assert_eq!(err.to_string(),"Extra derives are not supported by the error_chain backend");
+    }
+}
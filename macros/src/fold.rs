@@ -0,0 +1,317 @@
+//! Traversals over the parsed error definitions, for passes run before the expansion
+//!
+//! A [`Fold`] takes each node by value and returns its replacement, a [`Visit`]
+//! only looks at them. Every method defaults to walking into the children of
+//! its node, so implementations only override the nodes they care about. The
+//! free functions of the same name expose those defaults, for overrides that
+//! still need to recurse.
+//!
+//! The conversion of `quick!` entries is itself a pass, [`QuickErrorPass`], run
+//! before any other by [`quick::expand_with_passes`](crate::quick::expand_with_passes),
+//! so that later passes only ever see [`NormalError`]s.
+
+use crate::options::Options;
+use crate::quick::{ErrorsChildElementEnum, RootElementEnum, RootElementVec};
+use crate::quick::errors_child_element::{ErrorItem, NormalError, QuickError};
+use crate::quick::root_element::{ErrorsIdGroup, ForeignLink, ForeignLinksIdGroup, Link, LinksIdGroup, TypesIdGroup};
+
+/// Transformation of the definitions, taking and returning owned nodes
+pub trait Fold {
+    fn fold_root(&mut self, root: RootElementVec) -> syn::Result<RootElementVec> {
+        fold_root(self, root)
+    }
+
+    fn fold_root_element(&mut self, element: RootElementEnum) -> syn::Result<RootElementEnum> {
+        fold_root_element(self, element)
+    }
+
+    fn fold_types(&mut self, types: TypesIdGroup) -> syn::Result<TypesIdGroup> {
+        Ok(types)
+    }
+
+    fn fold_links(&mut self, links: LinksIdGroup) -> syn::Result<LinksIdGroup> {
+        fold_links(self, links)
+    }
+
+    fn fold_link(&mut self, link: Link) -> syn::Result<Link> {
+        Ok(link)
+    }
+
+    fn fold_foreign_links(&mut self, links: ForeignLinksIdGroup) -> syn::Result<ForeignLinksIdGroup> {
+        fold_foreign_links(self, links)
+    }
+
+    fn fold_foreign_link(&mut self, link: ForeignLink) -> syn::Result<ForeignLink> {
+        Ok(link)
+    }
+
+    fn fold_errors(&mut self, errors: ErrorsIdGroup) -> syn::Result<ErrorsIdGroup> {
+        fold_errors(self, errors)
+    }
+
+    fn fold_errors_child(&mut self, child: ErrorsChildElementEnum) -> syn::Result<ErrorsChildElementEnum> {
+        fold_errors_child(self, child)
+    }
+
+    /// Folds a `quick!` entry, which may be replaced by any kind of entry
+    fn fold_quick_error(&mut self, error: QuickError) -> syn::Result<ErrorsChildElementEnum> {
+        Ok(ErrorsChildElementEnum::QuickError(error))
+    }
+
+    fn fold_normal_error(&mut self, error: NormalError) -> syn::Result<NormalError> {
+        fold_normal_error(self, error)
+    }
+
+    fn fold_error_item(&mut self, item: ErrorItem) -> syn::Result<ErrorItem> {
+        Ok(item)
+    }
+}
+
+pub fn fold_root<F: Fold + ?Sized>(folder: &mut F, mut root: RootElementVec) -> syn::Result<RootElementVec> {
+    let items = std::mem::take(root.items_mut());
+    for item in items {
+        let item = folder.fold_root_element(item)?;
+        root.items_mut().push(item);
+    }
+    Ok(root)
+}
+
+pub fn fold_root_element<F: Fold + ?Sized>(folder: &mut F, element: RootElementEnum) -> syn::Result<RootElementEnum> {
+    Ok(match element {
+        RootElementEnum::TypesIdGroup(val) => RootElementEnum::TypesIdGroup(folder.fold_types(val)?),
+        RootElementEnum::LinksIdGroup(val) => RootElementEnum::LinksIdGroup(folder.fold_links(val)?),
+        RootElementEnum::ForeignLinksIdGroup(val) => RootElementEnum::ForeignLinksIdGroup(folder.fold_foreign_links(val)?),
+        RootElementEnum::ErrorsIdGroup(val) => RootElementEnum::ErrorsIdGroup(folder.fold_errors(val)?),
+        RootElementEnum::SkipMsgVariant(val) => RootElementEnum::SkipMsgVariant(val)
+    })
+}
+
+pub fn fold_links<F: Fold + ?Sized>(folder: &mut F, mut links: LinksIdGroup) -> syn::Result<LinksIdGroup> {
+    let items = std::mem::take(links.links_mut());
+    for item in items {
+        let item = folder.fold_link(item)?;
+        links.links_mut().push(item);
+    }
+    Ok(links)
+}
+
+pub fn fold_foreign_links<F: Fold + ?Sized>(folder: &mut F, mut links: ForeignLinksIdGroup) -> syn::Result<ForeignLinksIdGroup> {
+    let items = std::mem::take(links.links_mut());
+    for item in items {
+        let item = folder.fold_foreign_link(item)?;
+        links.links_mut().push(item);
+    }
+    Ok(links)
+}
+
+pub fn fold_errors<F: Fold + ?Sized>(folder: &mut F, mut errors: ErrorsIdGroup) -> syn::Result<ErrorsIdGroup> {
+    let items = std::mem::take(errors.items_mut());
+    for item in items {
+        let item = folder.fold_errors_child(item)?;
+        errors.items_mut().push(item);
+    }
+    Ok(errors)
+}
+
+pub fn fold_errors_child<F: Fold + ?Sized>(folder: &mut F, child: ErrorsChildElementEnum) -> syn::Result<ErrorsChildElementEnum> {
+    match child {
+        ErrorsChildElementEnum::QuickError(val) => folder.fold_quick_error(val),
+        ErrorsChildElementEnum::NormalError(val) => Ok(ErrorsChildElementEnum::NormalError(folder.fold_normal_error(val)?))
+    }
+}
+
+pub fn fold_normal_error<F: Fold + ?Sized>(folder: &mut F, mut error: NormalError) -> syn::Result<NormalError> {
+    for item in error.items_mut() {
+        *item = folder.fold_error_item(item.clone())?;
+    }
+    Ok(error)
+}
+
+/// Read-only traversal of the definitions
+pub trait Visit {
+    fn visit_root(&mut self, root: &RootElementVec) {
+        visit_root(self, root)
+    }
+
+    fn visit_root_element(&mut self, element: &RootElementEnum) {
+        visit_root_element(self, element)
+    }
+
+    fn visit_types(&mut self, _types: &TypesIdGroup) {}
+
+    fn visit_links(&mut self, links: &LinksIdGroup) {
+        for link in links.links() {
+            self.visit_link(link);
+        }
+    }
+
+    fn visit_link(&mut self, _link: &Link) {}
+
+    fn visit_foreign_links(&mut self, links: &ForeignLinksIdGroup) {
+        for link in links.links() {
+            self.visit_foreign_link(link);
+        }
+    }
+
+    fn visit_foreign_link(&mut self, _link: &ForeignLink) {}
+
+    fn visit_errors(&mut self, errors: &ErrorsIdGroup) {
+        for child in errors.items() {
+            self.visit_errors_child(child);
+        }
+    }
+
+    fn visit_errors_child(&mut self, child: &ErrorsChildElementEnum) {
+        match child {
+            ErrorsChildElementEnum::QuickError(ref val) => self.visit_quick_error(val),
+            ErrorsChildElementEnum::NormalError(ref val) => self.visit_normal_error(val)
+        }
+    }
+
+    fn visit_quick_error(&mut self, _error: &QuickError) {}
+
+    fn visit_normal_error(&mut self, error: &NormalError) {
+        for item in error.items() {
+            self.visit_error_item(item);
+        }
+    }
+
+    fn visit_error_item(&mut self, _item: &ErrorItem) {}
+}
+
+pub fn visit_root<V: Visit + ?Sized>(visitor: &mut V, root: &RootElementVec) {
+    for item in root.items() {
+        visitor.visit_root_element(item);
+    }
+}
+
+pub fn visit_root_element<V: Visit + ?Sized>(visitor: &mut V, element: &RootElementEnum) {
+    match element {
+        RootElementEnum::TypesIdGroup(ref val) => visitor.visit_types(val),
+        RootElementEnum::LinksIdGroup(ref val) => visitor.visit_links(val),
+        RootElementEnum::ForeignLinksIdGroup(ref val) => visitor.visit_foreign_links(val),
+        RootElementEnum::ErrorsIdGroup(ref val) => visitor.visit_errors(val),
+        RootElementEnum::SkipMsgVariant(_) => {}
+    }
+}
+
+/// Built-in pass converting every `quick!` entry into a [`NormalError`],
+/// after checking that no two errors define the same variant
+///
+/// The argument type and display style are taken from the options of the root it folds.
+#[derive(Default)]
+pub struct QuickErrorPass {
+    options: Options
+}
+
+impl Fold for QuickErrorPass {
+    fn fold_root(&mut self, root: RootElementVec) -> syn::Result<RootElementVec> {
+        self.options = root.options().clone();
+        fold_root(self, root)
+    }
+
+    fn fold_errors(&mut self, errors: ErrorsIdGroup) -> syn::Result<ErrorsIdGroup> {
+        errors.check_duplicates()?;
+        fold_errors(self, errors)
+    }
+
+    fn fold_quick_error(&mut self, error: QuickError) -> syn::Result<ErrorsChildElementEnum> {
+        Ok(ErrorsChildElementEnum::NormalError(error.into_normal_with(&self.options)))
+    }
+}
+
+
+#[cfg(test)]
+mod tests{
+    use std::assert_eq;
+    use quote::quote;
+    use syn::{Expr, ExprLit, Lit, LitStr, parse_quote};
+    use crate::fold::{Fold, Visit};
+    use crate::quick;
+    use crate::quick::errors_child_element::{ErrorItem, NormalError, QuickError};
+
+    struct DisplayPrefix;
+
+    impl Fold for DisplayPrefix {
+        fn fold_error_item(&mut self, mut item: ErrorItem) -> syn::Result<ErrorItem> {
+            if let ErrorItem::Display(ref mut display) = item {
+                if let Some(Expr::Lit(ExprLit { lit: Lit::Str(ref mut lit), .. })) = display.args.first_mut() {
+                    *lit = LitStr::new(&format!("[app] {}", lit.value()), lit.span());
+                }
+            }
+            Ok(item)
+        }
+    }
+
+    struct AllowDeadCode;
+
+    impl Fold for AllowDeadCode {
+        fn fold_normal_error(&mut self, mut error: NormalError) -> syn::Result<NormalError> {
+            error.attrs_mut().push(parse_quote!(#[allow(dead_code)]));
+            Ok(error)
+        }
+    }
+
+    #[test]
+    pub fn test_fold() {
+        let input = quote!{
+            errors {
+                NormalError1 {
+                    description("Error 1 Description")
+                    display("Error 1 Display")
+                }
+                quick!(QuickError1, "Error 2 Description", (arg1,))
+            }
+        };
+        let output = quick::main_with_passes(input, &mut [&mut DisplayPrefix, &mut AllowDeadCode]).unwrap();
+        let expected_output = quote!{
+            ::error_chain_utils::error_chain::error_chain!{
+                errors {
+                    #[allow(dead_code)]
+                    NormalError1 {
+                        description("Error 1 Description")
+                        display("[app] Error 1 Display")
+                    }
+                    #[allow(dead_code)]
+                    QuickError1 (arg1: String) {
+                        description("Error 2 Description")
+                        display("[app] Error 2 Description: {}", arg1)
+                    }
+                }
+            }
+        };
+        assert_eq!(output.to_string(),expected_output.to_string());
+    }
+
+    #[derive(Default)]
+    struct CountErrors {
+        quick: usize,
+        normal: usize
+    }
+
+    impl Visit for CountErrors {
+        fn visit_quick_error(&mut self, _error: &QuickError) {
+            self.quick += 1;
+        }
+
+        fn visit_normal_error(&mut self, _error: &NormalError) {
+            self.normal += 1;
+        }
+    }
+
+    #[test]
+    pub fn test_visit() {
+        let parsed = quick::parse(quote!{
+            errors {
+                NormalError1 {
+                    description("Error 1 Description")
+                }
+                quick!(QuickError1, "Error 2 Description")
+                quick!(QuickError2, "Error 3 Description")
+            }
+        }).unwrap();
+        let mut counter = CountErrors::default();
+        counter.visit_root(&parsed);
+        assert_eq!((counter.quick, counter.normal), (2, 1));
+    }
+}
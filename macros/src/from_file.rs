@@ -0,0 +1,684 @@
+//! This module implements the `error_chain_from_file!` macro, reading the definitions
+//! from a TOML or YAML file instead of the body of the invocation
+//!
+//! The file holds the same sections as an `error_chain_quick!` invocation:
+//!
+//! ```toml
+//! backend = "thiserror"         # Optional, like the options of `error_chain_quick!`
+//! crate = "::thiserror"
+//! skip_msg_variant = false
+//! catalog = true                # Emits the `ERROR_CATALOG` static
+//! catalog_path = "::utils::catalog"
+//!
+//! [types]                       # Optional
+//! error = "BuildError"
+//! error_kind = "BEKind"
+//! result_ext = "BETrait"
+//! result = "BEResult"           # Optional
+//!
+//! [[links]]
+//! name = "Another"
+//! error = "other::Error"
+//! kind = "other::ErrorKind"
+//! cfg = "unix"                  # Optional
+//!
+//! [[foreign_links]]
+//! name = "Io"
+//! error = "::std::io::Error"
+//!
+//! [[errors]]
+//! name = "NotFound"
+//! description = "Not found"
+//! args = ["path", "code: u32"]  # Arguments without a type are Strings
+//! display = "Not found: {} ({})" # Optional, built from the description like `quick!` otherwise
+//! code = "E0001"                # Optional, added to the documentation of the variant
+//! severity = "warning"          # Optional, like `code` listed in the catalog
+//! help = "Check the path"       # Optional
+//! ```
+
+use std::{fmt, fs, io};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use serde::{Deserialize, Deserializer};
+use serde::de::{MapAccess, SeqAccess, Visitor};
+use syn::{Ident, LitStr, Type};
+use yaml_rust::Event;
+use yaml_rust::parser::{MarkedEventReceiver, Parser};
+use yaml_rust::scanner::Marker;
+use crate::backend::BACKENDS;
+use crate::options::{Backend, DisplayStyle};
+use crate::quick::{self, RootElementVec};
+use crate::suggest;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileDefinitions {
+    backend: Option<String>,
+    #[serde(rename = "crate")]
+    crate_path: Option<String>,
+    #[serde(default)]
+    skip_msg_variant: bool,
+    #[serde(default)]
+    catalog: bool,
+    catalog_path: Option<String>,
+    types: Option<FileTypes>,
+    #[serde(default)]
+    links: Vec<FileLink>,
+    #[serde(default)]
+    foreign_links: Vec<FileForeignLink>,
+    #[serde(default)]
+    errors: Vec<FileError>
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileTypes {
+    error: String,
+    error_kind: String,
+    result_ext: String,
+    result: Option<String>
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileLink {
+    name: String,
+    error: String,
+    kind: String,
+    cfg: Option<String>
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileForeignLink {
+    name: String,
+    error: String,
+    cfg: Option<String>
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileError {
+    name: String,
+    description: String,
+    #[serde(default)]
+    args: Vec<String>,
+    display: Option<String>,
+    code: Option<String>,
+    severity: Option<String>,
+    help: Option<String>
+}
+
+/// An error in a definition file, located by line and column when possible
+#[derive(Debug)]
+pub struct DefinitionError {
+    pub path: PathBuf,
+    /// Line and column, both starting at 1
+    pub position: Option<(usize, usize)>,
+    pub message: String
+}
+
+impl fmt::Display for DefinitionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.position {
+            Some((line, column)) => write!(f, "{}:{}:{}: {}", self.path.display(), line, column, self.message),
+            None => write!(f, "{}: {}", self.path.display(), self.message)
+        }
+    }
+}
+
+impl std::error::Error for DefinitionError {}
+
+/// Formats accepted for definition files, chosen by extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Toml,
+    Yaml
+}
+
+impl Format {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "toml" => Some(Format::Toml),
+            "yaml" | "yml" => Some(Format::Yaml),
+            _ => None
+        }
+    }
+}
+
+/// Line and column, both starting at 1, of a byte offset of the text
+fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let line_start = text[..offset].rfind('\n').map_or(0, |index| index + 1);
+    (text[..offset].matches('\n').count() + 1, text[line_start..offset].chars().count() + 1)
+}
+
+/// A TOML value, along with the spans of the values it holds
+struct TomlValue(Vec<(String, toml::Spanned<TomlValue>)>);
+
+impl<'de> Deserialize<'de> for TomlValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ValueVisitor;
+
+        impl<'de> Visitor<'de> for ValueVisitor {
+            type Value = TomlValue;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a TOML value")
+            }
+
+            fn visit_bool<E>(self, _: bool) -> Result<TomlValue, E> {
+                Ok(TomlValue(vec![]))
+            }
+
+            fn visit_i64<E>(self, _: i64) -> Result<TomlValue, E> {
+                Ok(TomlValue(vec![]))
+            }
+
+            fn visit_u64<E>(self, _: u64) -> Result<TomlValue, E> {
+                Ok(TomlValue(vec![]))
+            }
+
+            fn visit_f64<E>(self, _: f64) -> Result<TomlValue, E> {
+                Ok(TomlValue(vec![]))
+            }
+
+            fn visit_str<E>(self, _: &str) -> Result<TomlValue, E> {
+                Ok(TomlValue(vec![]))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<TomlValue, A::Error> {
+                let mut values = vec![];
+                while let Some(value) = seq.next_element()? {
+                    values.push((values.len().to_string(), value));
+                }
+                Ok(TomlValue(values))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<TomlValue, A::Error> {
+                let mut values = vec![];
+                while let Some(key) = map.next_key()? {
+                    values.push((key, map.next_value()?));
+                }
+                Ok(TomlValue(values))
+            }
+        }
+
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+/// Path of the value at `key` in the value at `path`, such as `errors.0.name`
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() { key.to_string() } else { format!("{}.{}", path, key) }
+}
+
+/// Records the positions of the YAML values as they are parsed
+#[derive(Default)]
+struct YamlReceiver {
+    /// Paths of the mappings and sequences being parsed, with the pending key or the next index
+    stack: Vec<(String, YamlNext)>,
+    positions: Positions
+}
+
+/// What the next node of a mapping or sequence is
+enum YamlNext {
+    Key,
+    /// The value of the key
+    Value(String),
+    /// The element at the index
+    Index(usize)
+}
+
+impl YamlReceiver {
+    /// Path of the value starting, `None` when it is the key of a mapping
+    fn value_path(&mut self) -> Option<String> {
+        let (path, next) = match self.stack.last_mut() {
+            Some(val) => val,
+            None => return Some(String::new())
+        };
+        match std::mem::replace(next, YamlNext::Key) {
+            YamlNext::Key => None,
+            YamlNext::Value(key) => Some(join(path, &key)),
+            YamlNext::Index(index) => {
+                *next = YamlNext::Index(index + 1);
+                Some(join(path, &index.to_string()))
+            }
+        }
+    }
+}
+
+impl MarkedEventReceiver for YamlReceiver {
+    fn on_event(&mut self, event: Event, mark: Marker) {
+        let position = (mark.line(), mark.col() + 1);
+        match event {
+            Event::Scalar(value, ..) => match self.value_path() {
+                Some(path) => {
+                    self.positions.0.insert(path, position);
+                },
+                None => if let Some((_, next)) = self.stack.last_mut() {
+                    *next = YamlNext::Value(value);
+                }
+            },
+            Event::MappingStart(_) | Event::SequenceStart(_) => {
+                let path = self.value_path().unwrap_or_default();
+                self.positions.0.insert(path.clone(), position);
+                let next = if matches!(event, Event::MappingStart(_)) { YamlNext::Key } else { YamlNext::Index(0) };
+                self.stack.push((path, next));
+            },
+            Event::MappingEnd | Event::SequenceEnd => {
+                self.stack.pop();
+            },
+            Event::Alias(_) => {
+                self.value_path();
+            },
+            _ => ()
+        }
+    }
+}
+
+/// Line and column, both starting at 1, of the values of a definition file, keyed by
+/// their path, such as `errors.0.name`
+#[derive(Debug, Default)]
+pub struct Positions(HashMap<String, (usize, usize)>);
+
+impl Positions {
+    /// Locates the values of a definition file, none being found when it does not parse
+    pub fn new(text: &str, format: Format) -> Self {
+        match format {
+            Format::Toml => {
+                let mut positions = Positions::default();
+                if let Ok(value) = toml::from_str::<TomlValue>(text) {
+                    positions.add_toml(text, "", &value);
+                }
+                positions
+            },
+            Format::Yaml => {
+                let mut receiver = YamlReceiver::default();
+                match Parser::new(text.chars()).load(&mut receiver, false) {
+                    Ok(()) => receiver.positions,
+                    Err(_) => Positions::default()
+                }
+            }
+        }
+    }
+
+    fn add_toml(&mut self, text: &str, path: &str, value: &TomlValue) {
+        for (key, value) in &value.0 {
+            let path = join(path, key);
+            // Tables have no span, toml giving them an empty one
+            if value.end() > 0 {
+                self.0.insert(path.clone(), line_column(text, value.start()));
+            }
+            self.add_toml(text, &path, value.get_ref());
+        }
+    }
+
+    fn get(&self, path: &str) -> Option<(usize, usize)> {
+        self.0.get(path).copied()
+    }
+
+    /// Position of the name of the error at `index` in the `errors` list
+    pub fn error_name(&self, index: usize) -> Option<(usize, usize)> {
+        self.get(&format!("errors.{}.name", index))
+    }
+}
+
+/// Converts the deserialized definitions into an `error_chain_quick!` body
+struct Converter<'a> {
+    path: &'a Path,
+    positions: &'a Positions
+}
+
+/// A converted link, foreign link or error, along with where it is defined
+struct Entry {
+    section: &'static str,
+    tokens: TokenStream,
+    position: Option<(usize, usize)>
+}
+
+/// The converted definitions, the links and errors being kept apart so that
+/// a body can be built from the first ones only
+struct Converted {
+    head: TokenStream,
+    entries: Vec<Entry>,
+    skip_msg_variant: bool
+}
+
+impl Converted {
+    /// Body of an `error_chain_quick!` invocation holding the first `count` entries
+    fn body(&self, count: usize) -> TokenStream {
+        let mut tokens = self.head.clone();
+        for section in ["links", "foreign_links", "errors"] {
+            let entries: Vec<&TokenStream> = self.entries[..count].iter()
+                .filter(|entry| entry.section == section)
+                .map(|entry| &entry.tokens)
+                .collect();
+            if !entries.is_empty() {
+                let section = Ident::new(section, Span::call_site());
+                tokens.extend(quote!(#section { #(#entries)* }));
+            }
+        }
+        if self.skip_msg_variant {
+            tokens.extend(quote!(skip_msg_variant));
+        }
+        tokens
+    }
+
+    /// Checks that the body holding the first `count` entries parses and expands
+    fn check(&self, count: usize) -> syn::Result<()> {
+        quick::expand(quick::parse(self.body(count))?).map(|_| ())
+    }
+
+    /// Parses the body, after expanding it once to catch the errors found past parsing, such
+    /// as duplicate variants, which are located at the first entry the same error shows up
+    /// with, as the converted tokens carry no position
+    fn parse(&self) -> Result<RootElementVec, (Option<(usize, usize)>, syn::Error)> {
+        let all = self.entries.len();
+        self.check(all).map_err(|e| {
+            let message = e.to_string();
+            let count = (0..=all).find(|count| matches!(self.check(*count), Err(val) if val.to_string() == message));
+            let position = count.and_then(|count| count.checked_sub(1)).and_then(|index| self.entries[index].position);
+            (position, e)
+        })?;
+        quick::parse(self.body(all)).map_err(|e| (None, e))
+    }
+}
+
+impl Converter<'_> {
+    fn error(&self, key: &str, message: String) -> DefinitionError {
+        DefinitionError { path: self.path.to_path_buf(), position: self.positions.get(key), message }
+    }
+
+    fn parse<T: syn::parse::Parse>(&self, value: &str, what: &str, key: &str) -> Result<T, DefinitionError> {
+        syn::parse_str(value).map_err(|e| self.error(key, format!("Invalid {} '{}': {}", what, value, e)))
+    }
+
+    fn ident(&self, value: &str, key: &str) -> Result<Ident, DefinitionError> {
+        self.parse(value, "name", key)
+    }
+
+    fn cfg(&self, cfg: &Option<String>, key: &str) -> Result<TokenStream, DefinitionError> {
+        match cfg {
+            Some(val) => {
+                let predicate: TokenStream = self.parse(val, "cfg predicate", key)?;
+                Ok(quote!(#[cfg(#predicate)]))
+            },
+            None => Ok(TokenStream::new())
+        }
+    }
+
+    fn error_tokens(&self, index: usize, error: &FileError) -> Result<TokenStream, DefinitionError> {
+        let key = |field: &str| format!("errors.{}.{}", index, field);
+        let ident = self.ident(&error.name, &key("name"))?;
+        let mut names = vec![];
+        let mut args = vec![];
+        for (arg_index, arg) in error.args.iter().enumerate() {
+            let arg_key = key(&format!("args.{}", arg_index));
+            let (name, ty) = match arg.split_once(':') {
+                Some((name, ty)) => (name.trim(), self.parse::<Type>(ty.trim(), "argument type", &arg_key)?),
+                None => (arg.trim(), syn::parse_quote!(String))
+            };
+            let name_ident = self.ident(name, &arg_key)?;
+            args.push(quote!(#name_ident: #ty));
+            names.push(name_ident);
+        }
+        let description = LitStr::new(&error.description, Span::call_site());
+        let display = match error.display {
+            Some(ref val) => val.clone(),
+            None if names.is_empty() => error.description.clone(),
+            None => DisplayStyle::Colon.format(&error.description, &names.iter().map(|val| val.to_string()).collect::<Vec<_>>())
+        };
+        let display = LitStr::new(&display, Span::call_site());
+        let doc = error.code.as_ref().map(|code| {
+            let doc = format!(" Error code: `{}`", code);
+            quote!(#[doc = #doc])
+        });
+        let metadata: Vec<TokenStream> = [("code", &error.code), ("severity", &error.severity), ("help", &error.help)].iter()
+            .filter_map(|(key, value)| value.as_ref().map(|value| {
+                let key = Ident::new(key, Span::call_site());
+                quote!(#key = #value)
+            }))
+            .collect();
+        let catalog = if metadata.is_empty() { TokenStream::new() } else { quote!(#[catalog(#(#metadata),*)]) };
+        let args = if args.is_empty() { TokenStream::new() } else { quote!((#(#args),*)) };
+        Ok(quote!{
+            #doc
+            #catalog
+            #ident #args {
+                description(#description)
+                display(#display #(, #names)*)
+            }
+        })
+    }
+
+    fn convert(&self, defs: &FileDefinitions) -> Result<Converted, DefinitionError> {
+        let mut head = TokenStream::new();
+        if let Some(ref backend) = defs.backend {
+            if Backend::from_name(backend).is_none() {
+                let message = match suggest::closest(backend, &BACKENDS) {
+                    Some(val) => format!("Unknown backend '{}', did you mean '{}'?", backend, val),
+                    None => format!("Unknown backend '{}', expected one of {}", backend, BACKENDS.join(", "))
+                };
+                return Err(self.error("backend", message));
+            }
+            let backend = self.ident(backend, "backend")?;
+            head.extend(quote!(backend = #backend;));
+        }
+        if let Some(ref crate_path) = defs.crate_path {
+            let crate_path: syn::Path = self.parse(crate_path, "crate path", "crate")?;
+            head.extend(quote!(crate = #crate_path;));
+        }
+        if defs.catalog {
+            head.extend(quote!(catalog = true;));
+        }
+        if let Some(ref catalog_path) = defs.catalog_path {
+            let catalog_path: syn::Path = self.parse(catalog_path, "catalog path", "catalog_path")?;
+            head.extend(quote!(catalog_path = #catalog_path;));
+        }
+        if let Some(ref types) = defs.types {
+            let mut names = vec![(&types.error, "types.error"), (&types.error_kind, "types.error_kind"), (&types.result_ext, "types.result_ext")];
+            names.extend(types.result.as_ref().map(|val| (val, "types.result")));
+            let names = names.into_iter().map(|(val, key)| self.ident(val, key)).collect::<Result<Vec<_>, _>>()?;
+            head.extend(quote!(types { #(#names),*; }));
+        }
+        let mut entries = vec![];
+        for (index, link) in defs.links.iter().enumerate() {
+            let key = |field: &str| format!("links.{}.{}", index, field);
+            let name = self.ident(&link.name, &key("name"))?;
+            let error: syn::Path = self.parse(&link.error, "error path", &key("error"))?;
+            let kind: syn::Path = self.parse(&link.kind, "error kind path", &key("kind"))?;
+            let cfg = self.cfg(&link.cfg, &key("cfg"))?;
+            let tokens = quote!(#name(#error, #kind) #cfg;);
+            entries.push(Entry { section: "links", tokens, position: self.positions.get(&key("name")) });
+        }
+        for (index, link) in defs.foreign_links.iter().enumerate() {
+            let key = |field: &str| format!("foreign_links.{}.{}", index, field);
+            let name = self.ident(&link.name, &key("name"))?;
+            let error: syn::Path = self.parse(&link.error, "error path", &key("error"))?;
+            let cfg = self.cfg(&link.cfg, &key("cfg"))?;
+            let tokens = quote!(#name(#error) #cfg;);
+            entries.push(Entry { section: "foreign_links", tokens, position: self.positions.get(&key("name")) });
+        }
+        for (index, error) in defs.errors.iter().enumerate() {
+            let tokens = self.error_tokens(index, error)?;
+            entries.push(Entry { section: "errors", tokens, position: self.positions.error_name(index) });
+        }
+        Ok(Converted { head, entries, skip_msg_variant: defs.skip_msg_variant })
+    }
+}
+
+/// Parses the text of a definition file and checks that it expands, `path` only being used in errors
+pub fn parse_str(text: &str, format: Format, path: &Path) -> Result<RootElementVec, DefinitionError> {
+    let error = |position, message| DefinitionError { path: path.to_path_buf(), position, message };
+    let defs: FileDefinitions = match format {
+        Format::Toml => toml::from_str(text).map_err(|e| {
+            // The position is already given separately
+            let message = e.to_string();
+            let message = match message.rfind(" at line ") {
+                Some(index) => message[..index].to_string(),
+                None => message
+            };
+            error(e.line_col().map(|(line, column)| (line + 1, column + 1)), message)
+        })?,
+        Format::Yaml => serde_yaml::from_str(text).map_err(|e| {
+            error(e.location().map(|val| (val.line(), val.column())), e.to_string())
+        })?
+    };
+    let positions = Positions::new(text, format);
+    let converted = Converter { path, positions: &positions }.convert(&defs)?;
+    converted.parse().map_err(|(position, e)| error(position, e.to_string()))
+}
+
+/// Reads and parses a definition file, its format chosen by extension
+pub fn load(path: &Path) -> Result<RootElementVec, DefinitionError> {
+    let error = |message| DefinitionError { path: path.to_path_buf(), position: None, message };
+    let format = Format::from_path(path).ok_or_else(|| error("Expected a .toml, .yaml or .yml file".to_string()))?;
+    let text = fs::read_to_string(path).map_err(|e: io::Error| error(e.to_string()))?;
+    parse_str(&text, format, path)
+}
+
+/// Main function for the [`error_chain_from_file!`](../../error_chain_utils/macro.error_chain_from_file.html) macro
+///
+/// The path is relative to the directory of the manifest of the crate being built.
+pub fn main(input: TokenStream) -> syn::Result<TokenStream> {
+    let lit = syn::parse2::<LitStr>(input)?;
+    let mut path = PathBuf::from(lit.value());
+    if path.is_relative() {
+        if let Ok(dir) = std::env::var("CARGO_MANIFEST_DIR") {
+            path = Path::new(&dir).join(path);
+        }
+    }
+    let parsed = load(&path).map_err(|e| syn::Error::new(lit.span(), e))?;
+    let mut output = quick::expand(parsed)?;
+    // Makes the crate rebuild whenever the file changes
+    let path = LitStr::new(&path.to_string_lossy(), lit.span());
+    output.extend(quote!(const _: &[u8] = include_bytes!(#path);));
+    Ok(output)
+}
+
+
+#[cfg(test)]
+mod tests{
+    use std::assert_eq;
+    use std::path::Path;
+    use quote::quote;
+    use crate::catalog;
+    use crate::from_file::{parse_str, Format};
+    use crate::quick;
+
+    const TOML: &str = r#"
+[types]
+error = "BuildError"
+error_kind = "BEKind"
+result_ext = "BETrait"
+
+[[links]]
+name = "Another"
+error = "other::Error"
+kind = "other::ErrorKind"
+cfg = "unix"
+
+[[foreign_links]]
+name = "Io"
+error = "::std::io::Error"
+
+[[errors]]
+name = "NotFound"
+description = "Not found"
+args = ["path"]
+code = "E0001"
+severity = "warning"
+
+[[errors]]
+name = "Custom"
+description = "Custom"
+display = "Custom error {} ({})"
+args = ["code: u32", "name"]
+"#;
+
+    #[test]
+    pub fn test_toml() {
+        let parsed = parse_str(TOML, Format::Toml, Path::new("errors.toml")).unwrap();
+        let expected_output = quote!{
+            ::error_chain_utils::error_chain::error_chain!{
+                types { BuildError, BEKind, BETrait; }
+                links { Another(other::Error, other::ErrorKind) #[cfg(unix)]; }
+                foreign_links { Io(::std::io::Error); }
+                errors {
+                    #[doc = " Error code: `E0001`"]
+                    NotFound(path: String) {
+                        description("Not found")
+                        display("Not found: {}", path)
+                    }
+                    Custom(code: u32, name: String) {
+                        description("Custom")
+                        display("Custom error {} ({})", code, name)
+                    }
+                }
+            }
+        };
+        let entries = catalog::entries(&parsed).unwrap();
+        assert_eq!(entries[0].metadata.code.as_ref().unwrap().value(), "E0001");
+        assert_eq!(entries[0].metadata.severity.as_ref().unwrap().value(), "warning");
+        assert_eq!(quick::expand(parsed).unwrap().to_string(), expected_output.to_string());
+    }
+
+    #[test]
+    pub fn test_yaml() {
+        let yaml = r#"
+backend: thiserror
+errors:
+  - name: NotFound
+    description: Not found
+    args: [path]
+"#;
+        let parsed = parse_str(yaml, Format::Yaml, Path::new("errors.yaml")).unwrap();
+        let output = quick::expand(parsed).unwrap().to_string();
+        assert!(output.contains(&quote!(#[error("Not found: {}", .0)] NotFound(String),).to_string()));
+    }
+
+    #[test]
+    pub fn test_errors() {
+        let toml = "[[errors]]\nname = \"NotFound\"\ndescription = \"Not found\"\nargs = [\"code: u 32\"]\n";
+        let err = parse_str(toml, Format::Toml, Path::new("errors.toml")).unwrap_err();
+        assert_eq!(err.to_string(), "errors.toml:4:9: Invalid argument type 'u 32': unexpected token");
+
+        let toml = "[[errors]]\nname = \"NotFound\"\ndescriptin = \"Not found\"\n";
+        let err = parse_str(toml, Format::Toml, Path::new("errors.toml")).unwrap_err();
+        // toml only locates the table holding the field
+        assert_eq!(err.position, Some((1, 1)));
+        assert!(err.message.starts_with("unknown field `descriptin`"), "{}", err.message);
+
+        let yaml = "errors:\n  - name: NotFound\n    description: [1, 2]\n";
+        let err = parse_str(yaml, Format::Yaml, Path::new("errors.yaml")).unwrap_err();
+        assert_eq!(err.position.map(|(line, _)| line), Some(3));
+    }
+
+    #[test]
+    pub fn test_positions() {
+        // The value is located where it is given, not where it first appears
+        let toml = "[[errors]]\nname = \"A\"\ndescription = \"Not Found\"\n\n[[errors]]\nname = \"Not Found\"\ndescription = \"B\"\n";
+        let err = parse_str(toml, Format::Toml, Path::new("errors.toml")).unwrap_err();
+        assert_eq!(err.position, Some((6, 8)));
+
+        let yaml = "errors:\n  - name: A\n    description: Not Found\n  - name: Not Found\n    description: B\n";
+        let err = parse_str(yaml, Format::Yaml, Path::new("errors.yaml")).unwrap_err();
+        assert_eq!(err.position, Some((4, 11)));
+
+        let yaml = "foreign_links:\n  - name: Io\n    error: ::std::io::Error\nerrors:\n  - {name: NotFound, description: Io, args: [\"code: u 32\"]}\n";
+        let err = parse_str(yaml, Format::Yaml, Path::new("errors.yaml")).unwrap_err();
+        assert_eq!(err.position, Some((5, 46)));
+
+        // Errors found once the entries are converted are located at the entry they come from
+        let toml = "[[errors]]\nname = \"A\"\ndescription = \"A\"\n\n[[errors]]\nname = \"B\"\ndescription = \"B\"\n\n[[errors]]\nname = \"A\"\ndescription = \"Again\"\n";
+        let err = parse_str(toml, Format::Toml, Path::new("errors.toml")).unwrap_err();
+        assert_eq!(err.position, Some((10, 8)));
+        assert!(err.message.starts_with("Duplicate error variant 'A'"), "{}", err.message);
+
+        let yaml = "errors:\n  - name: A\n    description: A\n  - name: A\n    description: Again\n";
+        let err = parse_str(yaml, Format::Yaml, Path::new("errors.yaml")).unwrap_err();
+        assert_eq!(err.position, Some((4, 11)));
+    }
+}
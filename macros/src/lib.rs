@@ -1,8 +1,26 @@
 //! Library containing all the `proc-macro`-independent code for `error-chain-utils`
 //! See the full documentation there
+//!
+//! The parsed form of `error_chain!` and `error_chain_quick!` bodies is public,
+//! for tooling that needs to read error definitions: [`quick::parse`] returns a
+//! [`quick::RootElementVec`], whose nodes all implement `Parse` and `ToTokens`
+//! and write back the tokens they were parsed from. [`catalog::entries`] lists
+//! the errors of a body with their messages and `#[catalog(...)]` metadata.
+//!
+//! Other proc macros and build scripts can expand bodies through
+//! [`expander::QuickExpander`], with their own defaults and [`fold`] passes.
+//! Build scripts can also write the formatted expansion of a definition file
+//! with [`codegen::generate`].
 
-#![feature(try_blocks)]
-#![feature(extend_one)]
-#![feature(bool_to_option)]
-
-pub mod quick;
\ No newline at end of file
+pub mod catalog;
+pub mod expander;
+pub mod fold;
+pub mod codegen;
+pub mod from_file;
+pub mod options;
+pub mod pretty;
+pub mod quick;
+pub mod quick_mod;
+mod backend;
+mod diagnostic;
+mod suggest;
\ No newline at end of file
@@ -0,0 +1,263 @@
+//! Options accepted at the top of an `error_chain_quick!` invocation, before any section
+
+use proc_macro2::{Span, TokenStream};
+use syn::{Ident, LitBool, Path, Token, Type, parse::{Parse, ParseStream}, punctuated::Punctuated};
+use quote::{quote, ToTokens};
+pub use crate::backend::Backend;
+use crate::backend::BACKENDS;
+use crate::suggest;
+
+/// Names of all the supported options
+pub(crate) const OPTIONS: [&str; 4] = ["crate", "backend", "catalog", "catalog_path"];
+
+/// A single `key = value` option
+#[derive(Debug)]
+pub enum QuickOption {
+    /// `crate = ::path::to::error_chain`, the path through which the expansion refers to `error-chain`,
+    /// or to the crate of the selected backend
+    Crate(Span, Path),
+    /// `backend = thiserror`, `backend = std` or `backend = snafu`, the error library the definitions are expanded for
+    Backend(Span, Backend),
+    /// `catalog = true`, whether a `pub static ERROR_CATALOG` describing the errors is emitted
+    Catalog(Span, bool),
+    /// `catalog_path = ::path::to::catalog`, the path through which the catalog refers to
+    /// the `catalog` module of `error-chain-utils`
+    CatalogPath(Span, Path)
+}
+
+impl QuickOption {
+    /// Whether the input starts with an option rather than a section
+    pub fn peek(input: ParseStream) -> bool {
+        (input.peek(Token![crate]) || input.peek(Ident)) && input.peek2(Token![=])
+    }
+}
+
+impl Parse for QuickOption {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(Token![crate]) {
+            let key = input.parse::<Token![crate]>()?;
+            input.parse::<Token![=]>()?;
+            return Ok(QuickOption::Crate(key.span, input.call(Path::parse_mod_style)?));
+        }
+        let key = input.parse::<Ident>()?;
+        let name = key.to_string();
+        if name == "catalog" {
+            input.parse::<Token![=]>()?;
+            return Ok(QuickOption::Catalog(key.span(), input.parse::<LitBool>()?.value));
+        }
+        if name == "catalog_path" {
+            input.parse::<Token![=]>()?;
+            return Ok(QuickOption::CatalogPath(key.span(), input.call(Path::parse_mod_style)?));
+        }
+        if name == "backend" {
+            input.parse::<Token![=]>()?;
+            let value = input.parse::<Ident>()?;
+            return match Backend::from_name(&value.to_string()) {
+                Some(val) => Ok(QuickOption::Backend(key.span(), val)),
+                None => {
+                    let value_name = value.to_string();
+                    let message = match suggest::closest(&value_name, &BACKENDS) {
+                        Some(val) => format!("Unknown backend '{}', did you mean '{}'?", value_name, val),
+                        None => format!("Unknown backend '{}', expected one of {}", value_name, BACKENDS.join(", "))
+                    };
+                    Err(syn::Error::new(value.span(), message))
+                }
+            };
+        }
+        let message = match suggest::closest(&name, &OPTIONS) {
+            Some(val) => format!("Unknown option '{}', did you mean '{}'?", name, val),
+            None => format!("Unknown option '{}', expected one of {}", name, OPTIONS.join(", "))
+        };
+        Err(syn::Error::new(key.span(), message))
+    }
+}
+
+/// How the display of a `quick!` error shows its arguments after the description
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplayStyle {
+    /// `Description: {}, {}`, the default
+    Colon,
+    /// `Description ({}, {})`
+    Parens,
+    /// `Description: arg1 = {}, arg2 = {}`
+    Named
+}
+
+impl DisplayStyle {
+    /// Format string of the display of an error with the given description and arguments
+    pub fn format(self, description: &str, args: &[String]) -> String {
+        if args.is_empty() {
+            return description.to_string();
+        }
+        match self {
+            DisplayStyle::Colon => format!("{}: {}", description, vec!["{}"; args.len()].join(", ")),
+            DisplayStyle::Parens => format!("{} ({})", description, vec!["{}"; args.len()].join(", ")),
+            DisplayStyle::Named => {
+                let args: Vec<String> = args.iter().map(|arg| format!("{} = {{}}", arg)).collect();
+                format!("{}: {}", description, args.join(", "))
+            }
+        }
+    }
+}
+
+/// All the options given to an invocation
+///
+/// Only `crate`, `backend`, `catalog` and `catalog_path` can be written in the invocation itself, the others
+/// are set programmatically, through [`QuickExpander`](crate::expander::QuickExpander).
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    pub(crate) crate_path: Option<Path>,
+    pub(crate) backend: Option<Backend>,
+    pub(crate) arg_type: Option<Type>,
+    pub(crate) display_style: Option<DisplayStyle>,
+    pub(crate) derives: Vec<Path>,
+    pub(crate) catalog: Option<bool>,
+    pub(crate) catalog_path: Option<Path>
+}
+
+impl Options {
+    fn set(&mut self, option: QuickOption) -> syn::Result<()> {
+        match option {
+            QuickOption::Crate(span, path) => {
+                if self.crate_path.is_some() {
+                    return Err(syn::Error::new(span, "The 'crate' option was already given"));
+                }
+                self.crate_path = Some(path);
+            },
+            QuickOption::Backend(span, backend) => {
+                if self.backend.is_some() {
+                    return Err(syn::Error::new(span, "The 'backend' option was already given"));
+                }
+                self.backend = Some(backend);
+            },
+            QuickOption::Catalog(span, catalog) => {
+                if self.catalog.is_some() {
+                    return Err(syn::Error::new(span, "The 'catalog' option was already given"));
+                }
+                self.catalog = Some(catalog);
+            },
+            QuickOption::CatalogPath(span, path) => {
+                if self.catalog_path.is_some() {
+                    return Err(syn::Error::new(span, "The 'catalog_path' option was already given"));
+                }
+                self.catalog_path = Some(path);
+            }
+        }
+        Ok(())
+    }
+
+    /// Parses the `key = value;` options found before the sections of `error_chain_quick!`
+    pub fn parse_header(input: ParseStream) -> syn::Result<Self> {
+        let mut options = Options::default();
+        while QuickOption::peek(input) {
+            options.set(input.parse()?)?;
+            input.parse::<Token![;]>()?;
+        }
+        Ok(options)
+    }
+
+    /// Parses comma separated `key = value` options, as given to an attribute
+    pub fn parse_list(input: ParseStream) -> syn::Result<Self> {
+        let mut options = Options::default();
+        for option in Punctuated::<QuickOption, Token![,]>::parse_terminated(input)? {
+            options.set(option)?;
+        }
+        Ok(options)
+    }
+
+    /// Path to the crate of the backend, used by the generated code and helper items
+    pub fn crate_path(&self) -> Path {
+        match self.crate_path {
+            Some(ref val) => val.clone(),
+            None => match self.backend() {
+                Backend::ErrorChain => syn::parse2(quote!(::error_chain_utils::error_chain)).unwrap(),
+                Backend::Thiserror => syn::parse2(quote!(::thiserror)).unwrap(),
+                Backend::Std => syn::parse2(quote!(::std)).unwrap(),
+                Backend::Snafu => syn::parse2(quote!(::snafu)).unwrap()
+            }
+        }
+    }
+
+    pub fn backend(&self) -> Backend {
+        self.backend.unwrap_or(Backend::ErrorChain)
+    }
+
+    /// Type of the arguments of `quick!` errors, `String` by default
+    pub fn arg_type(&self) -> Type {
+        match self.arg_type {
+            Some(ref val) => val.clone(),
+            None => syn::parse2(quote!(String)).unwrap()
+        }
+    }
+
+    pub fn display_style(&self) -> DisplayStyle {
+        self.display_style.unwrap_or(DisplayStyle::Colon)
+    }
+
+    /// Extra derives for the generated error type, where the backend supports them
+    pub fn derives(&self) -> &[Path] {
+        &self.derives
+    }
+
+    /// Whether the `ERROR_CATALOG` static is emitted next to the definitions, `false` by default
+    pub fn catalog(&self) -> bool {
+        self.catalog.unwrap_or(false)
+    }
+
+    /// Path to the `catalog` module of `error-chain-utils`, whose types the `ERROR_CATALOG` static is made of
+    pub fn catalog_path(&self) -> Path {
+        match self.catalog_path {
+            Some(ref val) => val.clone(),
+            None => syn::parse2(quote!(::error_chain_utils::catalog)).unwrap()
+        }
+    }
+
+    /// Fills the options that were not given with the ones of `defaults`,
+    /// the derives of both being kept
+    ///
+    /// The crate path is only inherited when the backend is the one of `defaults`,
+    /// as it points at the crate of that backend.
+    pub fn merge_defaults(&mut self, defaults: &Options) {
+        if self.backend.is_none() {
+            self.backend = defaults.backend;
+        }
+        if self.crate_path.is_none() && self.backend() == defaults.backend() {
+            self.crate_path = defaults.crate_path.clone();
+        }
+        if self.arg_type.is_none() {
+            self.arg_type = defaults.arg_type.clone();
+        }
+        if self.display_style.is_none() {
+            self.display_style = defaults.display_style;
+        }
+        if self.catalog.is_none() {
+            self.catalog = defaults.catalog;
+        }
+        if self.catalog_path.is_none() {
+            self.catalog_path = defaults.catalog_path.clone();
+        }
+        let mut derives = defaults.derives.clone();
+        derives.append(&mut self.derives);
+        self.derives = derives;
+    }
+}
+
+impl ToTokens for Options {
+    /// Writes back the options that were given in the invocation, as a `key = value;` header
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        if let Some(ref path) = self.crate_path {
+            tokens.extend(quote!(crate = #path;));
+        }
+        if let Some(backend) = self.backend {
+            let name = Ident::new(backend.name(), Span::call_site());
+            tokens.extend(quote!(backend = #name;));
+        }
+        if let Some(catalog) = self.catalog {
+            let value = LitBool::new(catalog, Span::call_site());
+            tokens.extend(quote!(catalog = #value;));
+        }
+        if let Some(ref path) = self.catalog_path {
+            tokens.extend(quote!(catalog_path = #path;));
+        }
+    }
+}
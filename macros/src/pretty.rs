@@ -0,0 +1,220 @@
+//! Pretty printing of generated code, `error_chain!` invocations included
+
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{Attribute, Expr, Item, Meta, Path};
+use crate::quick::{self, ErrorsChildElementEnum, RootElementEnum, RootElementVec};
+use crate::quick::errors_child_element::{ErrorItem, NormalError};
+
+const INDENT: &str = "    ";
+
+/// Formats a whole file with `prettyplease`
+fn unparse(items: Vec<Item>) -> String {
+    prettyplease::unparse(&syn::File { shebang: None, attrs: vec![], items })
+}
+
+/// Formats the item and cuts `prefix` and `suffix` off the result,
+/// for printing fragments `prettyplease` has no entry point for
+fn fragment(item: TokenStream, prefix: &str, suffix: &str) -> String {
+    let item = syn::parse2(item).expect("Fragments are wrapped into valid items");
+    let output = unparse(vec![item]);
+    let output = output.trim_end();
+    output[prefix.len()..output.len() - suffix.len()].to_string()
+}
+
+fn expr(expr: &Expr) -> String {
+    fragment(quote!(const _: () = #expr;), "const _: () = ", ";")
+}
+
+/// Formats a type, or a path as the type it names
+pub fn ty(ty: &impl ToTokens) -> String {
+    fragment(quote!(type T = #ty;), "type T = ", ";")
+}
+
+fn exprs<'a>(exprs: impl IntoIterator<Item = &'a Expr>) -> String {
+    exprs.into_iter().map(expr).collect::<Vec<_>>().join(", ")
+}
+
+/// Attributes, one per line, doc attributes turned back into comments
+fn attrs(attrs: &[Attribute]) -> Vec<String> {
+    if attrs.is_empty() {
+        return vec![];
+    }
+    let output = unparse(vec![syn::parse2(quote!(#(#attrs)* struct S;)).unwrap()]);
+    let mut lines: Vec<String> = output.lines().map(str::to_string).collect();
+    lines.pop();
+    lines
+}
+
+/// Attributes written after a link, on its line
+///
+/// Doc attributes are kept as `#[doc = "..."]`, a comment would swallow the rest of the line.
+fn inline_attrs(attrs: &[Attribute]) -> String {
+    attrs.iter().map(|attr| match attr.parse_meta() {
+        Ok(Meta::NameValue(ref meta)) if meta.path.is_ident("doc") => {
+            let lit = &meta.lit;
+            format!(" #[doc = {}]", quote!(#lit))
+        },
+        _ => format!(" {}", self::attrs(std::slice::from_ref(attr)).join(" "))
+    }).collect()
+}
+
+/// Formats generated items
+pub fn items(tokens: TokenStream) -> syn::Result<String> {
+    Ok(unparse(syn::parse2::<syn::File>(tokens)?.items))
+}
+
+/// Lines of the body of an error, without indentation
+fn normal_error(error: &NormalError) -> Vec<String> {
+    let mut lines = attrs(error.attrs());
+    let args = match error.args() {
+        Some(args) => {
+            let args: Vec<String> = args.iter().map(|arg| format!("{}: {}", arg.ident, ty(&arg.ty))).collect();
+            format!("({})", args.join(", "))
+        },
+        None => String::new()
+    };
+    lines.push(format!("{}{} {{", error.ident(), args));
+    for item in error.items() {
+        lines.push(match item {
+            ErrorItem::Description(ref val) => format!("{}description({})", INDENT, expr(&val.expr)),
+            ErrorItem::Display(ref val) => match val.chained {
+                Some((_, _, ref chained)) => format!("{}display({}) -> ({})", INDENT, exprs(&val.args), exprs(chained)),
+                None => format!("{}display({})", INDENT, exprs(&val.args))
+            }
+        });
+    }
+    lines.push("}".to_string());
+    lines
+}
+
+/// Opens a section, closing it right away when it has no lines
+fn section(name: &str, lines: Vec<String>) -> Vec<String> {
+    if lines.is_empty() {
+        return vec![format!("{} {{}}", name)];
+    }
+    let mut output = vec![format!("{} {{", name)];
+    output.extend(lines.into_iter().map(|line| format!("{}{}", INDENT, line)));
+    output.push("}".to_string());
+    output
+}
+
+fn root_element(element: &RootElementEnum) -> Vec<String> {
+    match element {
+        RootElementEnum::TypesIdGroup(ref val) => section("types", match val.names() {
+            Some(names) => {
+                let mut idents = vec![names.error.to_string(), names.error_kind.to_string(), names.result_ext.to_string()];
+                idents.extend(names.result.as_ref().map(|val| val.to_string()));
+                vec![format!("{};", idents.join(", "))]
+            },
+            None => vec![]
+        }),
+        RootElementEnum::LinksIdGroup(ref val) => section("links", val.links().iter().map(|link| {
+            format!("{}({}, {}){};", link.variant, ty(&link.error_path), ty(&link.kind_path), inline_attrs(&link.attrs))
+        }).collect()),
+        RootElementEnum::ForeignLinksIdGroup(ref val) => section("foreign_links", val.links().iter().map(|link| {
+            format!("{}({}){};", link.variant, ty(&link.error_path), inline_attrs(&link.attrs))
+        }).collect()),
+        RootElementEnum::ErrorsIdGroup(ref val) => section("errors", val.items().iter().flat_map(|item| match item {
+            ErrorsChildElementEnum::NormalError(ref val) => normal_error(val),
+            // Only left over when the definitions were not expanded
+            ErrorsChildElementEnum::QuickError(ref val) => vec![val.to_token_stream().to_string()]
+        }).collect()),
+        RootElementEnum::SkipMsgVariant(ref val) => vec![val.to_string()]
+    }
+}
+
+/// Formats an `error_chain!` invocation, one section after the other
+pub fn error_chain(macro_path: &Path, root: &RootElementVec) -> String {
+    let mut output = format!("{}! {{\n", ty(macro_path));
+    for (index, element) in root.items().iter().enumerate() {
+        if index > 0 {
+            output.push('\n');
+        }
+        for line in root_element(element) {
+            output.push_str(INDENT);
+            output.push_str(&line);
+            output.push('\n');
+        }
+    }
+    output.push('}');
+    output
+}
+
+/// Formats the output of an expansion, which is either an `error_chain!` invocation
+/// or the items of another backend, followed by the `ERROR_CATALOG` static if any
+pub fn expansion(output: TokenStream) -> syn::Result<String> {
+    let file = syn::parse2::<syn::File>(output.clone())?;
+    if let [Item::Macro(ref item), ref rest @ ..] = file.items[..] {
+        if matches!(item.mac.path.segments.last(), Some(segment) if segment.ident == "error_chain") {
+            let mut formatted = error_chain(&item.mac.path, &quick::parse(item.mac.tokens.clone())?);
+            if !rest.is_empty() {
+                formatted.push_str("\n\n");
+                formatted.push_str(items(quote!(#(#rest)*))?.trim_end());
+            }
+            return Ok(formatted);
+        }
+    }
+    Ok(items(output)?.trim_end().to_string())
+}
+
+
+#[cfg(test)]
+mod tests{
+    use std::assert_eq;
+    use quote::quote;
+    use syn::parse_quote;
+    use crate::quick;
+    use crate::pretty;
+
+    #[test]
+    pub fn test_error_chain() {
+        let root = quick::parse(quote!{
+            types { BuildError, BEKind, BETrait, BEResult; }
+            links { Another(other::Error, other::ErrorKind) #[cfg(unix)]; }
+            foreign_links { Io(::std::io::Error); Fmt(::std::fmt::Error) #[doc = "Formatting error"]; }
+            errors {
+                /// Documented
+                Documented { description("Documented") display("Documented") }
+                WithArgs(path: String, code: Vec<u8>) {
+                    description("With arguments"),
+                    display("With arguments: {}, {:?}", path, code),
+                }
+                Chained(x: u8) { display(e) -> ("{}", e.description()) }
+            }
+            skip_msg_variant
+        }).unwrap();
+        let expected = r#"::error_chain::error_chain! {
+    types {
+        BuildError, BEKind, BETrait, BEResult;
+    }
+
+    links {
+        Another(other::Error, other::ErrorKind) #[cfg(unix)];
+    }
+
+    foreign_links {
+        Io(::std::io::Error);
+        Fmt(::std::fmt::Error) #[doc = "Formatting error"];
+    }
+
+    errors {
+        /// Documented
+        Documented {
+            description("Documented")
+            display("Documented")
+        }
+        WithArgs(path: String, code: Vec<u8>) {
+            description("With arguments")
+            display("With arguments: {}, {:?}", path, code)
+        }
+        Chained(x: u8) {
+            display(e) -> ("{}", e.description())
+        }
+    }
+
+    skip_msg_variant
+}"#;
+        assert_eq!(pretty::error_chain(&parse_quote!(::error_chain::error_chain), &root), expected);
+    }
+}
@@ -2,9 +2,14 @@
 //! See the full documentation there
 
 use std::fmt;
-use proc_macro2::{Delimiter, Group, TokenStream, TokenTree};
+use proc_macro2::{Delimiter, Group, Ident, TokenStream};
 use syn::{parse::{Parse, ParseStream, ParseBuffer}, parse2};
 use quote::{quote,ToTokens};
+use crate::backend::{self, Backend, Definitions};
+use crate::catalog::{self, StripMetadata};
+use crate::fold::{Fold, QuickErrorPass};
+use crate::options::{Options, QuickOption};
+use crate::suggest;
 
 trait TryParse {
     fn try_parse<T: Parse>(&self) -> syn::Result<T>;
@@ -19,66 +24,275 @@ impl TryParse for ParseBuffer<'_> {
     }
 }
 
-trait ProcessQuickError<T> {
-    fn process_quick_error(self) -> T;
-}
-
-mod errors_child_element {
+/// Nodes of the body of the `errors` section
+pub mod errors_child_element {
     use std::fmt;
-    use syn::{LitStr, parenthesized, parse::{Parse, ParseStream, ParseBuffer}, token, punctuated};
-    use proc_macro2::{Delimiter, Group, Ident, TokenStream, TokenTree};
-    use quote::{ToTokens, quote};
-    use crate::quick::{ProcessQuickError,TryParse};
+    use syn::{Attribute, Expr, ExprLit, ExprPath, Lit, LitStr, Token, Type, braced, parenthesized, parse_quote_spanned, parse::{Parse, ParseStream, Parser}, token, punctuated::{self, Punctuated}};
+    use proc_macro2::{Delimiter, Group, Ident, Span, TokenStream, TokenTree};
+    use quote::ToTokens;
+    use crate::diagnostic::{QuickDiagnostic, QuickErrorReason};
+    use crate::options::Options;
+    use crate::suggest;
 
-    #[derive(Debug)]
+    /// A single `name: Type` argument of an error
+    #[derive(Debug, Clone)]
+    pub struct ErrorArg {
+        pub ident: Ident,
+        pub colon_token: Token![:],
+        pub ty: Type
+    }
+
+    impl Parse for ErrorArg {
+        fn parse(input: ParseStream) -> syn::Result<Self> {
+            Ok(ErrorArg {
+                ident: input.parse()?,
+                colon_token: input.parse()?,
+                ty: input.parse()?
+            })
+        }
+    }
+
+    impl ToTokens for ErrorArg {
+        fn to_tokens(&self, tokens: &mut TokenStream) {
+            self.ident.to_tokens(tokens);
+            self.colon_token.to_tokens(tokens);
+            self.ty.to_tokens(tokens);
+        }
+    }
+
+    /// `description(expr)`, the description of an error
+    #[derive(Debug, Clone)]
+    pub struct Description {
+        pub ident: Ident,
+        pub paren_token: token::Paren,
+        pub expr: Expr
+    }
+
+    /// `display(format, args...)`, or `display(binding) -> (format, args...)`, the display of an error
+    #[derive(Debug, Clone)]
+    pub struct Display {
+        pub ident: Ident,
+        pub paren_token: token::Paren,
+        /// Format string and its arguments, or the binding of the error when `chained` is set
+        pub args: Punctuated<Expr, Token![,]>,
+        /// Format string and arguments of the `-> (...)` form
+        pub chained: Option<(Token![->], token::Paren, Punctuated<Expr, Token![,]>)>
+    }
+
+    /// An item of the body of an error
+    #[derive(Debug, Clone)]
+    #[allow(clippy::large_enum_variant)]
+    pub enum ErrorItem {
+        Description(Description),
+        Display(Display)
+    }
+
+    impl Parse for ErrorItem {
+        fn parse(input: ParseStream) -> syn::Result<Self> {
+            let ident = input.parse::<Ident>()?;
+            let content;
+            let paren_token = parenthesized!(content in input);
+            if ident == "description" {
+                Ok(ErrorItem::Description(Description { ident, paren_token, expr: content.parse()? }))
+            } else if ident == "display" {
+                let args = Punctuated::parse_terminated(&content)?;
+                let chained = if input.peek(Token![->]) {
+                    let arrow = input.parse::<Token![->]>()?;
+                    let chained_content;
+                    let chained_paren = parenthesized!(chained_content in input);
+                    Some((arrow, chained_paren, Punctuated::parse_terminated(&chained_content)?))
+                } else {
+                    None
+                };
+                Ok(ErrorItem::Display(Display { ident, paren_token, args, chained }))
+            } else {
+                let name = ident.to_string();
+                let message = match suggest::closest(&name, &ERROR_ITEMS) {
+                    Some(val) => format!("Unknown error item '{}', did you mean '{}'?", name, val),
+                    None => format!("Unknown error item '{}', expected one of {}", name, ERROR_ITEMS.join(", "))
+                };
+                Err(syn::Error::new(ident.span(), message))
+            }
+        }
+    }
+
+    impl ToTokens for ErrorItem {
+        fn to_tokens(&self, tokens: &mut TokenStream) {
+            match self {
+                Self::Description(ref val) => {
+                    val.ident.to_tokens(tokens);
+                    val.paren_token.surround(tokens, |tokens| val.expr.to_tokens(tokens));
+                },
+                Self::Display(ref val) => {
+                    val.ident.to_tokens(tokens);
+                    val.paren_token.surround(tokens, |tokens| val.args.to_tokens(tokens));
+                    if let Some((ref arrow, ref paren, ref args)) = val.chained {
+                        arrow.to_tokens(tokens);
+                        paren.surround(tokens, |tokens| args.to_tokens(tokens));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Items accepted in the body of an error
+    const ERROR_ITEMS: [&str; 2] = ["description", "display"];
+
+    /// An error written in the `error_chain!` syntax
+    #[derive(Debug, Clone)]
     pub struct NormalError {
+        attrs: Vec<Attribute>,
         ident: Ident,
-        args: Option<Group>,
-        body: Group
+        args: Option<(token::Paren, Punctuated<ErrorArg, Token![,]>)>,
+        brace_token: token::Brace,
+        /// Items along with their optional trailing comma, kept for round-tripping
+        items: Vec<(ErrorItem, Option<Token![,]>)>
     }
 
     impl Parse for NormalError {
         fn parse(input: ParseStream) -> syn::Result<Self> {
-            let ident = input.try_parse::<Ident>()?;
-            let first_group = input.try_parse::<Group>()?;
-            if first_group.delimiter() == Delimiter::Parenthesis {
-                let second_group = input.try_parse::<Group>()?;
-                if second_group.delimiter() == Delimiter::Brace {
-                    Ok(NormalError {
-                        ident,
-                        args: Some(first_group),
-                        body: second_group
-                    })
-                } else {
-                    Err(syn::Error::new(second_group.span_open(),"Unexpected Delimiter Here"))
-                }
-            } else if first_group.delimiter() == Delimiter::Brace {
-                Ok(NormalError {
-                    ident,
-                    args: None,
-                    body: first_group
-                })
+            let attrs = input.call(Attribute::parse_outer)?;
+            let ident = input.parse::<Ident>()?;
+            let args = if input.peek(token::Paren) {
+                let content;
+                let paren = parenthesized!(content in input);
+                Some((paren, Punctuated::parse_terminated(&content)?))
+            } else if input.peek(token::Brace) {
+                None
             } else {
-                Err(syn::Error::new(first_group.span_open(), "Unexpected delimiter here"))
+                return Err(input.error("Unexpected delimiter here"));
+            };
+            if !input.peek(token::Brace) {
+                return Err(input.error("Unexpected delimiter here"));
+            }
+            let content;
+            let brace_token = braced!(content in input);
+            let mut items = vec![];
+            while !content.is_empty() {
+                let item = content.parse::<ErrorItem>()?;
+                let comma = if content.peek(Token![,]) { Some(content.parse()?) } else { None };
+                items.push((item, comma));
             }
+            Ok(NormalError { attrs, ident, args, brace_token, items })
+        }
+    }
+
+    impl NormalError {
+        /// Builds an error from its parts, spanning the generated tokens at `span`
+        pub fn new(ident: Ident, args: Vec<ErrorArg>, items: Vec<ErrorItem>, span: Span) -> Self {
+            NormalError {
+                attrs: vec![],
+                ident,
+                args: if args.is_empty() { None } else { Some((token::Paren(span), args.into_iter().collect())) },
+                brace_token: token::Brace(span),
+                items: items.into_iter().map(|item| (item, None)).collect()
+            }
+        }
+
+        pub fn attrs(&self) -> &[Attribute] {
+            &self.attrs
+        }
+
+        pub fn ident(&self) -> &Ident {
+            &self.ident
+        }
+
+        /// Arguments of the error, `None` when it has no parentheses at all
+        pub fn args(&self) -> Option<&Punctuated<ErrorArg, Token![,]>> {
+            self.args.as_ref().map(|(_, args)| args)
+        }
+
+        pub fn items(&self) -> impl Iterator<Item = &ErrorItem> {
+            self.items.iter().map(|(item, _)| item)
+        }
+
+        pub fn description(&self) -> Option<&Description> {
+            self.items().find_map(|item| match item {
+                ErrorItem::Description(ref val) => Some(val),
+                _ => None
+            })
+        }
+
+        pub fn display(&self) -> Option<&Display> {
+            self.items().find_map(|item| match item {
+                ErrorItem::Display(ref val) => Some(val),
+                _ => None
+            })
+        }
+
+        pub fn attrs_mut(&mut self) -> &mut Vec<Attribute> {
+            &mut self.attrs
+        }
+
+        pub fn ident_mut(&mut self) -> &mut Ident {
+            &mut self.ident
+        }
+
+        pub fn args_mut(&mut self) -> Option<&mut Punctuated<ErrorArg, Token![,]>> {
+            self.args.as_mut().map(|(_, args)| args)
+        }
+
+        pub fn items_mut(&mut self) -> impl Iterator<Item = &mut ErrorItem> {
+            self.items.iter_mut().map(|(item, _)| item)
         }
     }
 
     impl ToTokens for NormalError {
         fn to_tokens(&self, tokens: &mut TokenStream) {
-            tokens.extend_one(TokenTree::from(self.ident.clone()));
-            match self.args.clone() {
-                Some(val) => tokens.extend_one(TokenTree::from(val)),
-                _ => ()
-            };
-            tokens.extend_one(TokenTree::from(self.body.clone()));
+            for attr in &self.attrs {
+                attr.to_tokens(tokens);
+            }
+            self.ident.to_tokens(tokens);
+            if let Some((ref paren, ref args)) = self.args {
+                paren.surround(tokens, |tokens| args.to_tokens(tokens));
+            }
+            self.brace_token.surround(tokens, |tokens| {
+                for (item, comma) in &self.items {
+                    item.to_tokens(tokens);
+                    comma.to_tokens(tokens);
+                }
+            });
         }
     }
 
+    /// A `quick!(Variant, "Description", (args...))` entry, before its conversion into a [`NormalError`]
+    #[derive(Clone)]
     pub struct QuickError {
+        /// Outer attributes, given to the variant once converted
+        attrs: Vec<Attribute>,
         err_ident: Ident,
         desc: LitStr,
-        inner_args: punctuated::Punctuated<Ident,token::Comma>
+        inner_args: punctuated::Punctuated<Ident,token::Comma>,
+        /// Comma closing the entry, kept so that it prints back as written
+        trailing_comma: Option<Token![,]>,
+        /// Spans of the `quick` ident, the entry's parentheses and the argument list's
+        /// parentheses, given to the tokens generated from them
+        quick_span: Span,
+        paren_span: Span,
+        args_span: Span
+    }
+
+    impl QuickError {
+        pub fn attrs(&self) -> &[Attribute] {
+            &self.attrs
+        }
+
+        pub fn attrs_mut(&mut self) -> &mut Vec<Attribute> {
+            &mut self.attrs
+        }
+
+        pub fn ident(&self) -> &Ident {
+            &self.err_ident
+        }
+
+        pub fn description(&self) -> &LitStr {
+            &self.desc
+        }
+
+        /// Names of the arguments, which are all of type `String`
+        pub fn args(&self) -> &Punctuated<Ident, Token![,]> {
+            &self.inner_args
+        }
     }
 
     impl fmt::Debug for QuickError {
@@ -110,169 +324,224 @@ mod errors_child_element {
                 }
             }
     
-            #[derive(Debug)]
-            struct QuickErrorDebug<'a> {
-                err_ident: &'a Ident,
-                desc: LitStrDebug<'a>,
-                inner_args: PunctuatedDebug<'a,Ident,token::Comma>
-            }
-    
-            (QuickErrorDebug { 
-                err_ident: &self.err_ident,
-                desc: LitStrDebug { inner: &self.desc },
-                inner_args: PunctuatedDebug {
-                    inner: &self.inner_args
-                }
-            }).fmt(fmt)
+            fmt.debug_struct("QuickError")
+                .field("attrs", &self.attrs)
+                .field("err_ident", &self.err_ident)
+                .field("desc", &LitStrDebug { inner: &self.desc })
+                .field("inner_args", &PunctuatedDebug { inner: &self.inner_args })
+                .finish()
         }
 
     }
 
-    fn parse_parens(input: ParseStream) -> syn::Result<ParseBuffer> {
-        let contents;
-        parenthesized!(contents in input);
-        Ok(contents)
+    /// Parses the arguments inside `quick!( ... )`, pointing at the first offending token
+    fn parse_quick_args(args: ParseStream, quick_span: Span, paren: &Group) -> Result<QuickError,QuickDiagnostic> {
+        let close = paren.span_close();
+        let span_of = |args: ParseStream| if args.is_empty() { close } else { args.span() };
+        let err_ident = args.parse::<Ident>()
+            .map_err(|_| QuickDiagnostic::new(span_of(args), QuickErrorReason::MissingIdent))?;
+        args.parse::<token::Comma>()
+            .map_err(|_| QuickDiagnostic::new(span_of(args), QuickErrorReason::MissingComma))?;
+        let desc = args.parse::<LitStr>()
+            .map_err(|_| QuickDiagnostic::new(span_of(args), QuickErrorReason::NonLiteralDescription))?;
+        let mut inner_args = punctuated::Punctuated::new();
+        let mut args_span = paren.span();
+        let mut trailing_comma = None;
+        if !args.is_empty() {
+            let comma = args.parse::<token::Comma>()
+                .map_err(|_| QuickDiagnostic::new(span_of(args), QuickErrorReason::MissingComma))?;
+            if args.is_empty() {
+                trailing_comma = Some(comma);
+            } else if args.peek(token::Paren) {
+                let group = args.parse::<Group>()
+                    .map_err(|_| QuickDiagnostic::new(span_of(args), QuickErrorReason::BadArgumentList))?;
+                args_span = group.span();
+                inner_args = punctuated::Punctuated::<Ident,token::Comma>::parse_terminated
+                    .parse2(group.stream())
+                    .map_err(|e| QuickDiagnostic::new(e.span(), QuickErrorReason::BadArgumentList))?;
+                if !args.is_empty() {
+                    trailing_comma = Some(args.parse::<token::Comma>()
+                        .map_err(|_| QuickDiagnostic::new(span_of(args), QuickErrorReason::MissingComma))?);
+                }
+            }
+            if !args.is_empty() {
+                return Err(QuickDiagnostic::new(span_of(args), QuickErrorReason::UnexpectedTokens));
+            }
+        }
+        Ok(QuickError {
+            attrs: vec![],
+            err_ident,
+            desc,
+            inner_args,
+            trailing_comma,
+            quick_span,
+            paren_span: paren.span(),
+            args_span
+        })
     }
 
-    fn try_parse_parens(input: ParseStream) -> syn::Result<ParseBuffer> {
-        match parse_parens(&mut input.fork()) {
-            Ok(_) => Ok(parse_parens(input).unwrap()),
-            Err(e) => Err(e.clone()) 
+    impl QuickError {
+        /// Whether the input starts with a `quick!` entry, its attributes included
+        pub fn peek(input: ParseStream) -> bool {
+            let fork = input.fork();
+            if fork.call(Attribute::parse_outer).is_err() {
+                return false;
+            }
+            match fork.parse::<Ident>() {
+                Ok(ident) => ident == "quick" && fork.peek(token::Bang),
+                Err(_) => false
+            }
+        }
+
+        /// Parses a whole `quick!( ... )` entry
+        ///
+        /// The entry's tokens are consumed even when it is malformed, so that
+        /// parsing can carry on with the next entry.
+        pub fn parse_entry(input: ParseStream) -> syn::Result<Result<QuickError,QuickDiagnostic>> {
+            let attrs = input.call(Attribute::parse_outer)?;
+            let ident = input.parse::<Ident>()?;
+            if ident != "quick" {
+                return Err(syn::Error::new(ident.span(),"Ident was not 'quick'"));
+            }
+            let bang = input.parse::<token::Bang>()?;
+            if input.is_empty() {
+                return Ok(Err(QuickDiagnostic::new(bang.span, QuickErrorReason::MissingParens)));
+            }
+            let group = match input.parse::<TokenTree>()? {
+                TokenTree::Group(val) if val.delimiter() == Delimiter::Parenthesis => val,
+                other => return Ok(Err(QuickDiagnostic::new(other.span(), QuickErrorReason::MissingParens)))
+            };
+            let parser = |args: ParseStream| {
+                let result = parse_quick_args(args, ident.span(), &group).map(|val| QuickError { attrs: attrs.clone(), ..val });
+                // Whatever is left after a failure is already covered by the diagnostic
+                args.parse::<TokenStream>()?;
+                Ok(result)
+            };
+            parser.parse2(group.stream())
         }
     }
 
     impl Parse for QuickError {
         fn parse(input: ParseStream) -> syn::Result<Self> {
-            let ident = input.try_parse::<Ident>()?;
-            (ident.to_string() == "quick")
-                .then_some(())
-                .ok_or(syn::Error::new(ident.span(),"Ident was not 'quick'"))?;
-            input.try_parse::<token::Bang>()?;
-            let late_fail: Result<QuickError,syn::Error> = try {
-                let args = &mut try_parse_parens(input)?;
-                let err_ident = args.try_parse::<Ident>()?;
-                args.try_parse::<token::Comma>()?;
-                let desc = args.try_parse::<LitStr>()?;
-                let mut invalid_inner_args = false;
-                let optional_paren: syn::Result<punctuated::Punctuated<Ident,token::Comma>> = try{
-                    args.try_parse::<token::Comma>()?;
-                    let inner_args_unparsed = &mut try_parse_parens(args)?;
-                    match punctuated::Punctuated::parse_terminated(&inner_args_unparsed) {
-                        Ok(val) => Ok(val),
-                        Err(e) => {
-                            invalid_inner_args = true;
-                            Err(e)
-                        }
-                    }?
-                };
-                let inner_args = match optional_paren {
-                    Ok(val) => Ok(val),
-                    Err(_) => {
-                        if invalid_inner_args {
-                            Err(syn::Error::new(args.span(),"INV_QUICK"))
-                        } else {
-                            Ok(punctuated::Punctuated::new())
-                        }
-                    }
-                };
-
-                match args.try_parse::<token::Comma>() { _ => ()};
-                args.is_empty().then_some(()).ok_or(syn::Error::new(args.span(),"INV_QUICK"))?;
+            Ok(QuickError::parse_entry(input)??)
+        }
+    }
 
-                QuickError {
-                    err_ident,
-                    desc,
-                    inner_args: inner_args?
+    impl ToTokens for QuickError {
+        fn to_tokens(&self, tokens: &mut TokenStream) {
+            for attr in &self.attrs {
+                attr.to_tokens(tokens);
+            }
+            Ident::new("quick", self.quick_span).to_tokens(tokens);
+            Token![!](self.quick_span).to_tokens(tokens);
+            token::Paren(self.paren_span).surround(tokens, |tokens| {
+                self.err_ident.to_tokens(tokens);
+                Token![,](self.desc.span()).to_tokens(tokens);
+                self.desc.to_tokens(tokens);
+                if !self.inner_args.is_empty() {
+                    Token![,](self.args_span).to_tokens(tokens);
+                    token::Paren(self.args_span).surround(tokens, |tokens| self.inner_args.to_tokens(tokens));
                 }
-            };
-            late_fail.or_else(|i:syn::Error| {Err(syn::Error::new(i.span(),"INV_QUICK"))})
-            
+                self.trailing_comma.to_tokens(tokens);
+            });
         }
     }
 
-    impl ProcessQuickError<NormalError> for QuickError {
-        fn process_quick_error(self) -> NormalError {
+    /// Expression of a single path, spanned at `ident`
+    fn path_expr(ident: &Ident) -> Expr {
+        Expr::Path(ExprPath { attrs: vec![], qself: None, path: ident.clone().into() })
+    }
+
+    /// Expression of a string literal
+    fn lit_expr(lit: LitStr) -> Expr {
+        Expr::Lit(ExprLit { attrs: vec![], lit: Lit::Str(lit) })
+    }
+
+    impl QuickError {
+        /// Converts the entry into the error it stands for
+        pub fn into_normal(self) -> NormalError {
+            self.into_normal_with(&Options::default())
+        }
+
+        /// Converts the entry into the error it stands for, with the argument
+        /// type and display style of `options`
+        pub fn into_normal_with(self, options: &Options) -> NormalError {
+            // Every generated token takes the span of the `quick!` tokens it comes from,
+            // so that diagnostics in the expanded `error_chain!` point at the entry
             let ident = self.err_ident;
-            let mut args_token_stream = TokenStream::new();
-            let mut first_arg = true;
-            for arg in self.inner_args.clone() {
-                if first_arg {
-                    first_arg = false;
-                } else {
-                    args_token_stream.extend(quote!(, ));
+            let mut args = Punctuated::new();
+            for arg in &self.inner_args {
+                if !args.is_empty() {
+                    args.push_punct(Token![,](arg.span()));
                 }
-                args_token_stream.extend_one(TokenTree::from(arg));
-                args_token_stream.extend(quote!( : String));
-            }
-            let args;
-            let are_args_empty = args_token_stream.is_empty();
-            if are_args_empty {
-                args = None;
-            } else {
-                args = Some(Group::new(Delimiter::Parenthesis,args_token_stream));
+                args.push_value(ErrorArg {
+                    ident: arg.clone(),
+                    colon_token: Token![:](arg.span()),
+                    ty: match options.arg_type {
+                        Some(ref val) => val.clone(),
+                        None => parse_quote_spanned!(arg.span()=> String)
+                    }
+                });
             }
 
-            let mut body_token_stream = TokenStream::new();
-            body_token_stream.extend(quote!(description));
-            body_token_stream.extend_one(TokenTree::from(Group::new(Delimiter::Parenthesis,self.desc.to_token_stream())));
-            body_token_stream.extend(quote!(display));
-            
-            let mut display_args_token_stream = TokenStream::new();
-            if are_args_empty {
-                display_args_token_stream.extend(self.desc.to_token_stream());
+            let description = Description {
+                ident: Ident::new("description", self.quick_span),
+                paren_token: token::Paren(self.desc.span()),
+                expr: lit_expr(self.desc.clone())
+            };
+
+            let mut display_args = Punctuated::new();
+            if self.inner_args.is_empty() {
+                display_args.push_value(lit_expr(self.desc.clone()));
             } else {
-                let mut format_str: String = self.desc.value();
-                format_str += ":";
-                for _ in self.inner_args.clone() {
-                    format_str += " {},"
-                }
-                format_str.pop();
-                display_args_token_stream.extend_one(LitStr::new(format_str.as_str(),self.desc.span()).into_token_stream());
+                let names: Vec<String> = self.inner_args.iter().map(|arg| arg.to_string()).collect();
+                let format_str = options.display_style().format(&self.desc.value(), &names);
+                display_args.push_value(lit_expr(LitStr::new(format_str.as_str(),self.desc.span())));
             }
-
-            for arg in self.inner_args {
-                display_args_token_stream.extend(quote!(,));
-                display_args_token_stream.extend_one(TokenTree::from(arg));
+            for arg in &self.inner_args {
+                display_args.push_punct(Token![,](arg.span()));
+                display_args.push_value(path_expr(arg));
             }
+            let display = Display {
+                ident: Ident::new("display", self.quick_span),
+                paren_token: token::Paren(self.desc.span()),
+                args: display_args,
+                chained: None
+            };
 
-            body_token_stream.extend_one(TokenTree::from(Group::new(Delimiter::Parenthesis,display_args_token_stream)));
-            let body = Group::new(Delimiter::Brace,body_token_stream);
-            NormalError { ident, args, body }
+            NormalError {
+                attrs: self.attrs,
+                ident,
+                args: if args.is_empty() { None } else { Some((token::Paren(self.args_span), args)) },
+                brace_token: token::Brace(self.paren_span),
+                items: vec![(ErrorItem::Description(description), None), (ErrorItem::Display(display), None)]
+            }
         }
     }
 }
 
+/// An entry of the `errors` section
 #[derive(Debug)]
-enum ErrorsChildElementEnum {
+pub enum ErrorsChildElementEnum {
     QuickError(errors_child_element::QuickError),
     NormalError(errors_child_element::NormalError)
 }
 
 impl Parse for ErrorsChildElementEnum {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        match input.try_parse() as syn::Result<errors_child_element::QuickError> {
-            Ok(val) => return Ok(Self::QuickError(val)),
-            Err(e) => {
-                if e.to_string() == "INV_QUICK" {
-                    return Err(e)
-                } else {
-                    ()
-                }
-            }
-        };
-        match input.try_parse() as syn::Result<errors_child_element::NormalError> {
-            Ok(val) => return Ok(Self::NormalError(val)),
-            Err(_) => ()
-        };
-        Err(input.error("Could not parse as ErrorsChildElementEnum"))
+        if errors_child_element::QuickError::peek(input) {
+            return Ok(Self::QuickError(input.parse()?))
+        }
+        Ok(Self::NormalError(input.parse()?))
     }
 }
 
-impl ProcessQuickError<ErrorsChildElementEnum> for ErrorsChildElementEnum {
-    fn process_quick_error(self) -> ErrorsChildElementEnum {
+impl ErrorsChildElementEnum {
+    /// Name of the error variant this element defines
+    pub fn ident(&self) -> &Ident {
         match self {
-            Self::QuickError(val) => Self::NormalError(val.process_quick_error()),
-            _ => self
+            Self::QuickError(ref val) => val.ident(),
+            Self::NormalError(ref val) => val.ident()
         }
     }
 }
@@ -281,16 +550,41 @@ impl ToTokens for ErrorsChildElementEnum {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         match self {
             Self::NormalError(ref val) => val.to_tokens(tokens),
-            Self::QuickError(ref _val) => panic!("Not all QuickError structs were converted to NormalError ones")
+            Self::QuickError(ref val) => val.to_tokens(tokens)
         }
     }
 }
 
-mod root_element {
-    use quote::ToTokens;
-    use syn::{braced, parse::{Parse, ParseStream}};
+/// Nodes of the root sections
+pub mod root_element {
+    use quote::{quote, ToTokens};
+    use syn::{Attribute, Path, Token, braced, parenthesized, token, parse::{Parse, ParseStream}};
     use proc_macro2::{Delimiter, Group, Ident, TokenStream, TokenTree};
-    use crate::quick::{ErrorsChildElementEnum, ProcessQuickError, TryParse};
+    use crate::quick::{ErrorsChildElementEnum, SHORTHANDS, TryParse};
+    use crate::quick::errors_child_element::QuickError;
+    use crate::suggest;
+
+    fn push_error(errors: &mut Option<syn::Error>, error: syn::Error) {
+        match errors {
+            Some(ref mut val) => val.combine(error),
+            None => *errors = Some(error)
+        }
+    }
+
+    /// Consumes a macro-like entry that is not a supported shorthand, and
+    /// returns the error for it, suggesting the closest shorthand if any
+    fn parse_unknown_shorthand(input: ParseStream) -> syn::Result<syn::Error> {
+        let ident = input.parse::<Ident>()?;
+        input.parse::<Token![!]>()?;
+        if !input.is_empty() {
+            input.parse::<TokenTree>()?;
+        }
+        let message = match suggest::closest(&ident.to_string(), &SHORTHANDS) {
+            Some(val) => format!("Unknown macro '{}!', did you mean '{}!'?", ident, val),
+            None => format!("Unknown macro '{}!', expected one of {}", ident, SHORTHANDS.map(|val| format!("'{}!'", val)).join(", "))
+        };
+        Ok(syn::Error::new(ident.span(), message))
+    }
 
     #[derive(Debug)]
     pub struct ErrorsIdGroup {
@@ -301,20 +595,43 @@ mod root_element {
     impl Parse for ErrorsIdGroup {
         fn parse(input: ParseStream) -> syn::Result<Self> {
             let ident = input.try_parse::<Ident>()?;
-            if ident.to_string() == "errors" {
+            if ident == "errors" {
                 let errors;
                 braced!(errors in input);
                 let mut items = vec![];
+                let mut diagnostics: Option<syn::Error> = None;
                 if errors.is_empty() {
                     Err(errors.error("Unexpected end of input"))
                 } else {
                     while !errors.is_empty() {
-                        items.push(errors.try_parse::<ErrorsChildElementEnum>()?);
+                        if QuickError::peek(&errors) {
+                            // Malformed entries are reported together once the whole block is read
+                            match QuickError::parse_entry(&errors)? {
+                                Ok(val) => items.push(ErrorsChildElementEnum::QuickError(val)),
+                                Err(diagnostic) => push_error(&mut diagnostics, diagnostic.into())
+                            }
+                        } else if errors.peek(syn::Ident) && errors.peek2(Token![!]) {
+                            push_error(&mut diagnostics, parse_unknown_shorthand(&errors)?);
+                        } else {
+                            match errors.try_parse::<ErrorsChildElementEnum>() {
+                                Ok(val) => items.push(val),
+                                Err(e) => match diagnostics {
+                                    Some(mut val) => {
+                                        val.combine(e);
+                                        return Err(val);
+                                    },
+                                    None => return Err(e)
+                                }
+                            }
+                        }
+                    }
+                    match diagnostics {
+                        Some(e) => Err(e),
+                        None => Ok(ErrorsIdGroup {
+                            ident,
+                            items
+                        })
                     }
-                    Ok(ErrorsIdGroup {
-                        ident,
-                        items
-                    })
                 }
             } else {
                 Err(syn::Error::new(ident.span(),"Expected 'errors'"))
@@ -322,150 +639,381 @@ mod root_element {
         }
     }
 
-    impl ProcessQuickError<ErrorsIdGroup> for ErrorsIdGroup {
-        fn process_quick_error(self) -> ErrorsIdGroup {
-            let mut new_items = vec![];
-            for item in self.items {
-                new_items.push(item.process_quick_error());
+    impl ErrorsIdGroup {
+        pub fn items(&self) -> &[ErrorsChildElementEnum] {
+            &self.items
+        }
+
+        pub fn items_mut(&mut self) -> &mut Vec<ErrorsChildElementEnum> {
+            &mut self.items
+        }
+
+        /// Checks that no two errors, `quick!` or normal, define the same variant
+        pub fn check_duplicates(&self) -> syn::Result<()> {
+            let mut seen: Vec<&Ident> = vec![];
+            let mut errors: Option<syn::Error> = None;
+            for item in &self.items {
+                let ident = item.ident();
+                match seen.iter().find(|val| **val == ident) {
+                    Some(first) => {
+                        let mut e = syn::Error::new(ident.span(), format!("Duplicate error variant '{}'", ident));
+                        e.combine(syn::Error::new(first.span(), format!("'{}' was first defined here", ident)));
+                        match errors {
+                            Some(ref mut val) => val.combine(e),
+                            None => errors = Some(e)
+                        }
+                    },
+                    None => seen.push(ident)
+                }
             }
-            ErrorsIdGroup {
-                ident: self.ident,
-                items: new_items
+            match errors {
+                Some(e) => Err(e),
+                None => Ok(())
             }
         }
     }
 
     impl ToTokens for ErrorsIdGroup {
         fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
-            tokens.extend_one(TokenTree::from(self.ident.clone()));
+            self.ident.to_tokens(tokens);
             let mut group_token_stream = TokenStream::new();
             for item in &self.items {
                 item.to_tokens(&mut group_token_stream);
             }
-            tokens.extend_one(TokenTree::from(Group::new(Delimiter::Brace,group_token_stream)));
+            Group::new(Delimiter::Brace,group_token_stream).to_tokens(tokens);
         }
     }
 
     #[derive(Debug)]
-    pub struct OtherIdGroup {
+    pub struct TypesIdGroup {
         ident: Ident,
-        body: Option<Group>
+        brace: token::Brace,
+        names: Option<TypeNames>
     }
-    
-    impl Parse for OtherIdGroup {
+
+    /// Names given to the generated types, the `Result` alias being optional
+    #[derive(Debug)]
+    pub struct TypeNames {
+        pub error: Ident,
+        pub error_kind: Ident,
+        pub result_ext: Ident,
+        pub result: Option<Ident>
+    }
+
+    impl TypesIdGroup {
+        pub fn names(&self) -> Option<&TypeNames> {
+            self.names.as_ref()
+        }
+    }
+
+    impl Parse for TypesIdGroup {
         fn parse(input: ParseStream) -> syn::Result<Self> {
-            let ident = input.try_parse::<Ident>()?;
-            let body = input.try_parse() as syn::Result<Group>;
-            match body {
-                Ok(val) => Ok(OtherIdGroup {
-                    ident,
-                    body: Some(val)
-                }),
-                Err(_) => Ok(OtherIdGroup {
-                    ident,
-                    body: None
-                })
+            let ident = input.parse::<Ident>()?;
+            let content;
+            let brace = braced!(content in input);
+            if content.is_empty() {
+                return Ok(TypesIdGroup { ident, brace, names: None });
             }
+            let error = content.parse::<Ident>()?;
+            content.parse::<Token![,]>()?;
+            let error_kind = content.parse::<Ident>()?;
+            content.parse::<Token![,]>()?;
+            let result_ext = content.parse::<Ident>()?;
+            let result = if content.peek(Token![,]) {
+                content.parse::<Token![,]>()?;
+                Some(content.parse::<Ident>()?)
+            } else {
+                None
+            };
+            content.parse::<Token![;]>()?;
+            if !content.is_empty() {
+                return Err(content.error("Unexpected tokens after the type names"));
+            }
+            Ok(TypesIdGroup {
+                ident,
+                brace,
+                names: Some(TypeNames { error, error_kind, result_ext, result })
+            })
         }
     }
 
-    impl ToTokens for OtherIdGroup {
+    impl ToTokens for TypesIdGroup {
         fn to_tokens(&self, tokens: &mut TokenStream) {
-            tokens.extend_one(TokenTree::from(self.ident.clone()));
-            match self.body.clone() {
-                Some(val) => tokens.extend_one(TokenTree::from(val)),
-                None => ()
-            };
+            self.ident.to_tokens(tokens);
+            self.brace.surround(tokens, |tokens| {
+                if let Some(ref names) = self.names {
+                    let TypeNames { error, error_kind, result_ext, result } = names;
+                    tokens.extend(quote!(#error, #error_kind, #result_ext));
+                    if let Some(result) = result {
+                        tokens.extend(quote!(, #result));
+                    }
+                    tokens.extend(quote!(;));
+                }
+            });
+        }
+    }
+
+    /// A single `Variant(ErrorPath, ErrorKindPath) #[attrs];` entry of the `links` section
+    #[derive(Debug)]
+    pub struct Link {
+        pub variant: Ident,
+        pub error_path: Path,
+        pub kind_path: Path,
+        pub attrs: Vec<Attribute>
+    }
+
+    impl Parse for Link {
+        fn parse(input: ParseStream) -> syn::Result<Self> {
+            let variant = input.parse::<Ident>()?;
+            let content;
+            parenthesized!(content in input);
+            let error_path = content.parse::<Path>()?;
+            content.parse::<Token![,]>()?;
+            let kind_path = content.parse::<Path>()?;
+            if !content.is_empty() {
+                return Err(content.error("Expected the error path and the error kind path only"));
+            }
+            let attrs = input.call(Attribute::parse_outer)?;
+            input.parse::<Token![;]>()?;
+            Ok(Link { variant, error_path, kind_path, attrs })
+        }
+    }
+
+    impl ToTokens for Link {
+        fn to_tokens(&self, tokens: &mut TokenStream) {
+            let Link { variant, error_path, kind_path, attrs } = self;
+            tokens.extend(quote!(#variant(#error_path, #kind_path) #(#attrs)*;));
+        }
+    }
+
+    #[derive(Debug)]
+    pub struct LinksIdGroup {
+        ident: Ident,
+        brace: token::Brace,
+        links: Vec<Link>
+    }
+
+    impl LinksIdGroup {
+        pub fn links(&self) -> &[Link] {
+            &self.links
+        }
+
+        pub fn links_mut(&mut self) -> &mut Vec<Link> {
+            &mut self.links
+        }
+    }
+
+    impl Parse for LinksIdGroup {
+        fn parse(input: ParseStream) -> syn::Result<Self> {
+            let ident = input.parse::<Ident>()?;
+            let content;
+            let brace = braced!(content in input);
+            let mut links = vec![];
+            while !content.is_empty() {
+                links.push(content.parse::<Link>()?);
+            }
+            Ok(LinksIdGroup { ident, brace, links })
+        }
+    }
+
+    impl ToTokens for LinksIdGroup {
+        fn to_tokens(&self, tokens: &mut TokenStream) {
+            self.ident.to_tokens(tokens);
+            self.brace.surround(tokens, |tokens| {
+                for link in &self.links {
+                    link.to_tokens(tokens);
+                }
+            });
+        }
+    }
+
+    /// A single `Variant(ErrorPath) #[attrs];` entry of the `foreign_links` section
+    #[derive(Debug)]
+    pub struct ForeignLink {
+        pub variant: Ident,
+        pub error_path: Path,
+        pub attrs: Vec<Attribute>
+    }
+
+    impl Parse for ForeignLink {
+        fn parse(input: ParseStream) -> syn::Result<Self> {
+            let variant = input.parse::<Ident>()?;
+            let content;
+            parenthesized!(content in input);
+            let error_path = content.parse::<Path>()?;
+            if !content.is_empty() {
+                return Err(content.error("Expected the error path only"));
+            }
+            let attrs = input.call(Attribute::parse_outer)?;
+            input.parse::<Token![;]>()?;
+            Ok(ForeignLink { variant, error_path, attrs })
+        }
+    }
+
+    impl ToTokens for ForeignLink {
+        fn to_tokens(&self, tokens: &mut TokenStream) {
+            let ForeignLink { variant, error_path, attrs } = self;
+            tokens.extend(quote!(#variant(#error_path) #(#attrs)*;));
+        }
+    }
+
+    #[derive(Debug)]
+    pub struct ForeignLinksIdGroup {
+        ident: Ident,
+        brace: token::Brace,
+        links: Vec<ForeignLink>
+    }
+
+    impl ForeignLinksIdGroup {
+        pub fn links(&self) -> &[ForeignLink] {
+            &self.links
+        }
+
+        pub fn links_mut(&mut self) -> &mut Vec<ForeignLink> {
+            &mut self.links
+        }
+    }
+
+    impl Parse for ForeignLinksIdGroup {
+        fn parse(input: ParseStream) -> syn::Result<Self> {
+            let ident = input.parse::<Ident>()?;
+            let content;
+            let brace = braced!(content in input);
+            let mut links = vec![];
+            while !content.is_empty() {
+                links.push(content.parse::<ForeignLink>()?);
+            }
+            Ok(ForeignLinksIdGroup { ident, brace, links })
+        }
+    }
+
+    impl ToTokens for ForeignLinksIdGroup {
+        fn to_tokens(&self, tokens: &mut TokenStream) {
+            self.ident.to_tokens(tokens);
+            self.brace.surround(tokens, |tokens| {
+                for link in &self.links {
+                    link.to_tokens(tokens);
+                }
+            });
         }
     }
 }
 
+/// Macro-like shorthands accepted inside the `errors` section
+pub(crate) const SHORTHANDS: [&str; 1] = ["quick"];
+
+/// Root sections accepted by `error_chain!`
+pub(crate) const SECTIONS: [&str; 5] = ["types", "links", "foreign_links", "errors", "skip_msg_variant"];
+
+/// A root section of an `error_chain!` or `error_chain_quick!` body
 #[derive(Debug)]
-enum RootElementEnum {
+pub enum RootElementEnum {
+    TypesIdGroup(root_element::TypesIdGroup),
+    LinksIdGroup(root_element::LinksIdGroup),
+    ForeignLinksIdGroup(root_element::ForeignLinksIdGroup),
     ErrorsIdGroup(root_element::ErrorsIdGroup),
-    OtherIdGroup(root_element::OtherIdGroup)
+    SkipMsgVariant(Ident)
 }
 
 impl Parse for RootElementEnum {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        match input.try_parse() as syn::Result<root_element::ErrorsIdGroup> {
-            Ok(val) => Ok(RootElementEnum::ErrorsIdGroup(val)),
-            Err(e) => {
-                if e.to_string() == "INV_QUICK" {
-                    return Err(e);
-                }
-                match input.try_parse() as syn::Result<root_element::OtherIdGroup> {
-                    Ok(val) => Ok(RootElementEnum::OtherIdGroup(val)),
-                    Err(e) => Err(e)
-                }
+        let ident = input.fork().parse::<Ident>()?;
+        match ident.to_string().as_str() {
+            "types" => Ok(RootElementEnum::TypesIdGroup(input.parse()?)),
+            "links" => Ok(RootElementEnum::LinksIdGroup(input.parse()?)),
+            "foreign_links" => Ok(RootElementEnum::ForeignLinksIdGroup(input.parse()?)),
+            "errors" => Ok(RootElementEnum::ErrorsIdGroup(input.parse()?)),
+            "skip_msg_variant" => Ok(RootElementEnum::SkipMsgVariant(input.parse()?)),
+            "derive" => Err(syn::Error::new(ident.span(), "The 'derive' section is not supported by error-chain 0.12")),
+            "skip_msg_impl" => Err(syn::Error::new(ident.span(), "Unknown section 'skip_msg_impl', did you mean 'skip_msg_variant'?")),
+            name => {
+                let message = match suggest::closest(name, &SECTIONS) {
+                    Some(val) => format!("Unknown section '{}', did you mean '{}'?", name, val),
+                    None => format!("Unknown section '{}', expected one of {}", name, SECTIONS.join(", "))
+                };
+                Err(syn::Error::new(ident.span(), message))
             }
         }
     }
 }
 
-impl ProcessQuickError<RootElementEnum> for RootElementEnum {
-    fn process_quick_error(self) -> RootElementEnum {
-        match self {
-            Self::ErrorsIdGroup(val) => Self::ErrorsIdGroup(val.process_quick_error()),
-            _ => self
-        }
-    }
-}
-
 impl ToTokens for RootElementEnum {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         match self {
+            Self::TypesIdGroup(ref val) => val.to_tokens(tokens),
+            Self::LinksIdGroup(ref val) => val.to_tokens(tokens),
+            Self::ForeignLinksIdGroup(ref val) => val.to_tokens(tokens),
             Self::ErrorsIdGroup(ref val) => val.to_tokens(tokens),
-            Self::OtherIdGroup(ref val)  => val.to_tokens(tokens)
+            Self::SkipMsgVariant(ref val) => val.to_tokens(tokens)
         }
     }
 }
 
-struct RootElementVec {
+/// The whole body of an `error_chain!` or `error_chain_quick!` invocation, options included
+pub struct RootElementVec {
+    options: Options,
     items: Vec<RootElementEnum>
 }
 
+impl RootElementVec {
+    /// Replaces the options, for callers that take them from somewhere other than the header
+    pub fn set_options(&mut self, options: Options) {
+        self.options = options;
+    }
+
+    pub fn options(&self) -> &Options {
+        &self.options
+    }
+
+    pub fn options_mut(&mut self) -> &mut Options {
+        &mut self.options
+    }
+
+    pub fn items(&self) -> &[RootElementEnum] {
+        &self.items
+    }
+
+    pub fn items_mut(&mut self) -> &mut Vec<RootElementEnum> {
+        &mut self.items
+    }
+}
+
 impl fmt::Debug for RootElementVec {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
-        self.items.fmt(f)
+        f.debug_struct("RootElementVec")
+            .field("options", &self.options)
+            .field("items", &self.items)
+            .finish()
     }
 }
 
 impl Parse for RootElementVec {
     fn parse(input: ParseStream) -> syn::Result<Self> {
+        let options = Options::parse_header(input)?;
         let mut items = vec![];
         if input.is_empty() {
             Err(input.error("Unexpected end of input"))
         } else {
             while !input.is_empty() {
+                if QuickOption::peek(input) {
+                    return Err(input.error("Options must be given before any section"));
+                }
                 items.push(RootElementEnum::parse(input)?);
             }
             Ok(RootElementVec {
+                options,
                 items
             })
         }
     }
 }
 
-impl ProcessQuickError<RootElementVec> for RootElementVec {
-    fn process_quick_error(self) -> RootElementVec {
-        let mut new_items = vec![];
-        for item in self.items {
-            new_items.push(item.process_quick_error());
-        }
-        RootElementVec {
-            items: new_items
-        }
-    }
-}
-
 impl ToTokens for RootElementVec {
     fn to_tokens(&self, tokens: &mut TokenStream) {
-        let mut group_token_stream = TokenStream::new();
+        self.options.to_tokens(tokens);
         for item in &self.items {
-            item.to_tokens(&mut group_token_stream);
+            item.to_tokens(tokens);
         }
-        tokens.extend_one(TokenTree::from(Group::new(Delimiter::Brace,group_token_stream)));
     }
 }
 
@@ -473,29 +1021,69 @@ impl ToTokens for RootElementVec {
 
 /// Main function for the [`error_chain_quick!`](../../error_chain_utils/macro.error_chain_quick.html) macro
 pub fn main(input: TokenStream) -> syn::Result<TokenStream> {
-    let parsed_input: RootElementVec = match parse2(input) {
-        Ok(val) => val,
-        Err(e) => {
-            if e.to_string() == "INV_QUICK" {
-                let mut new_e = syn::Error::new(e.span(),"Invalid 'quick!()' macro");
-                new_e.combine(e);
-                return Err(new_e);
-            } else {
-                return Err(e);
+    expand(parse(input)?)
+}
+
+/// Like [`main`], running each of `passes` over the definitions before expanding them
+pub fn main_with_passes(input: TokenStream, passes: &mut [&mut dyn Fold]) -> syn::Result<TokenStream> {
+    expand_with_passes(parse(input)?, passes)
+}
+
+/// Parses the body of an `error_chain_quick!` invocation
+pub fn parse(input: TokenStream) -> syn::Result<RootElementVec> {
+    parse2(input)
+}
+
+/// Converts all `quick!` errors and expands into the final `error_chain!` invocation,
+/// or into the code of the selected backend
+pub fn expand(parsed_input: RootElementVec) -> syn::Result<TokenStream> {
+    expand_with_passes(parsed_input, &mut [])
+}
+
+/// Like [`expand`], running each of `passes` in order once the `quick!` errors are converted
+pub fn expand_with_passes(parsed_input: RootElementVec, passes: &mut [&mut dyn Fold]) -> syn::Result<TokenStream> {
+    let mut transformed_input: RootElementVec = QuickErrorPass::default().fold_root(parsed_input)?;
+    for pass in passes.iter_mut() {
+        transformed_input = pass.fold_root(transformed_input)?;
+    }
+    let entries = catalog::entries(&transformed_input)?;
+    transformed_input = StripMetadata.fold_root(transformed_input)?;
+    let mut output = expand_backend(&transformed_input)?;
+    if transformed_input.options.catalog() {
+        output.extend(catalog::expand(&entries, &transformed_input.options.catalog_path()));
+    }
+    Ok(output)
+}
+
+/// Expands definitions whose `quick!` errors are all converted, for the selected backend
+fn expand_backend(transformed_input: &RootElementVec) -> syn::Result<TokenStream> {
+    let crate_path = transformed_input.options.crate_path();
+    match transformed_input.options.backend() {
+        Backend::ErrorChain => {
+            if let Some(derive) = transformed_input.options.derives().first() {
+                return Err(syn::Error::new_spanned(derive, "Extra derives are not supported by the error_chain backend"));
             }
-        }
-    };
-    let transformed_input: RootElementVec = parsed_input.process_quick_error();
-    let mut output_stream: TokenStream = TokenStream::new();
-    output_stream.extend(quote!(::error_chain::error_chain!));
-    transformed_input.to_tokens(&mut output_stream);
-    Ok(output_stream)
+            let mut output_stream: TokenStream = TokenStream::new();
+            output_stream.extend(quote!(#crate_path::error_chain!));
+            // The options are only meant for this crate, so they are left out of the invocation
+            let mut group_token_stream = TokenStream::new();
+            for item in &transformed_input.items {
+                item.to_tokens(&mut group_token_stream);
+            }
+            Group::new(Delimiter::Brace,group_token_stream).to_tokens(&mut output_stream);
+            Ok(output_stream)
+        },
+        Backend::Thiserror => backend::thiserror::expand(&crate_path, &Definitions::new(transformed_input)?),
+        Backend::Std => backend::standard::expand(&crate_path, &Definitions::new(transformed_input)?),
+        Backend::Snafu => backend::snafu::expand(&crate_path, &Definitions::new(transformed_input)?)
+    }
 }
 
 
 #[cfg(test)]
 mod tests{
     use std::assert_eq;
+    use proc_macro2::{TokenStream, TokenTree};
     use quote::quote;
     use crate::quick;
     #[test]
@@ -523,7 +1111,7 @@ mod tests{
         };
         let output = quick::main(input).unwrap();
         let expected_output = quote!{
-            ::error_chain::error_chain!{
+            ::error_chain_utils::error_chain::error_chain!{
                 types {
                     BuildError, BEKind, BETrait, BEResult;
                 }
@@ -558,4 +1146,318 @@ mod tests{
         };
         assert_eq!(output.to_string(),expected_output.to_string(),"Actual output and Expected output did not match.\n Expected Output: \n{:#?}\n Actual Output: \n{:#?}\n",expected_output,output);
     }
-}
\ No newline at end of file
+
+    #[test]
+    pub fn test_sections() {
+        let input = quote!{
+            types {
+                BuildError, BEKind, BETrait;
+            }
+            links {
+                Another(other::Error, other::ErrorKind) #[cfg(unix)];
+            }
+            foreign_links {
+                Io(::std::io::Error);
+                Fmt(::std::fmt::Error) #[doc = "Formatting error"];
+            }
+            skip_msg_variant
+            errors {
+                quick!(QuickError1, "Error 1 Description: Zero arguments")
+            }
+        };
+        let output = quick::main(input).unwrap();
+        let expected_output = quote!{
+            ::error_chain_utils::error_chain::error_chain!{
+                types {
+                    BuildError, BEKind, BETrait;
+                }
+                links {
+                    Another(other::Error, other::ErrorKind) #[cfg(unix)];
+                }
+                foreign_links {
+                    Io(::std::io::Error);
+                    Fmt(::std::fmt::Error) #[doc = "Formatting error"];
+                }
+                skip_msg_variant
+                errors {
+                    QuickError1 {
+                        description("Error 1 Description: Zero arguments")
+                        display("Error 1 Description: Zero arguments")
+                    }
+                }
+            }
+        };
+        assert_eq!(output.to_string(),expected_output.to_string());
+    }
+
+    #[test]
+    pub fn test_invalid_sections() {
+        let err = quick::main(quote!{ tpyes { BuildError, BEKind, BETrait; } }).unwrap_err();
+        assert_eq!(err.to_string(),"Unknown section 'tpyes', did you mean 'types'?");
+        let err = quick::main(quote!{ types { BuildError, BEKind, BETrait } }).unwrap_err();
+        assert_eq!(err.to_string(),"expected `;`");
+        let err = quick::main(quote!{ foreign_links { Io(::std::io::Error) } }).unwrap_err();
+        assert_eq!(err.to_string(),"expected `;`");
+    }
+
+    #[test]
+    pub fn test_duplicates() {
+        let input = quote!{
+            errors {
+                Foo {
+                    description("Normal Foo")
+                    display("Normal Foo")
+                }
+                quick!(Foo, "Quick Foo")
+            }
+        };
+        let err = quick::main(input).unwrap_err();
+        let messages: Vec<String> = err.into_iter().map(|e| e.to_string()).collect();
+        assert_eq!(messages,vec!["Duplicate error variant 'Foo'","'Foo' was first defined here"]);
+    }
+
+    #[test]
+    pub fn test_invalid_quick() {
+        let input = quote!{
+            errors {
+                quick!(QuickError1 "Missing comma")
+                quick!(QuickError2, not_a_literal)
+                quick!(QuickError3, "Bad arguments", (arg1, "arg2"))
+                quick!(QuickError4, "Valid")
+                quick![QuickError5, "Wrong delimiter"]
+                quick!(QuickError6, "Leftover tokens", (arg1), arg2)
+            }
+        };
+        let err = quick::main(input).unwrap_err();
+        let messages: Vec<String> = err.into_iter().map(|e| e.to_string()).collect();
+        assert_eq!(messages,vec![
+            "Invalid 'quick!()' macro: expected ','",
+            "Invalid 'quick!()' macro: the description must be a string literal",
+            "Invalid 'quick!()' macro: the arguments must be a parenthesized list of identifiers",
+            "Invalid 'quick!()' macro: expected '(' after 'quick!'",
+            "Invalid 'quick!()' macro: unexpected tokens after the last argument"
+        ]);
+    }
+
+    #[test]
+    pub fn test_misspelled_quick() {
+        let input = quote!{
+            errors {
+                quik!(QuickError1, "Misspelled")
+                Quick!(QuickError2, "Capitalized")
+                other!(QuickError3, "Unrelated")
+            }
+        };
+        let err = quick::main(input).unwrap_err();
+        let messages: Vec<String> = err.into_iter().map(|e| e.to_string()).collect();
+        assert_eq!(messages,vec![
+            "Unknown macro 'quik!', did you mean 'quick!'?",
+            "Unknown macro 'Quick!', did you mean 'quick!'?",
+            "Unknown macro 'other!', expected one of 'quick!'"
+        ]);
+    }
+
+    #[test]
+    pub fn test_spans() {
+        fn idents(stream: TokenStream, found: &mut Vec<(String, usize)>) {
+            for tree in stream {
+                match tree {
+                    TokenTree::Ident(val) => found.push((val.to_string(), val.span().start().line)),
+                    TokenTree::Group(val) => idents(val.stream(), found),
+                    _ => ()
+                }
+            }
+        }
+        let input: TokenStream = "errors {\n    quick!(QuickError1, \"Description\", (arg1,))\n}".parse().unwrap();
+        let output = quick::main(input).unwrap();
+        // Only the contents of the `errors` section come from the quick! entry
+        let errors_body = output.into_iter()
+            .filter_map(|tree| match tree { TokenTree::Group(val) => Some(val.stream()), _ => None })
+            .flat_map(|stream| stream.into_iter())
+            .filter_map(|tree| match tree { TokenTree::Group(val) => Some(val.stream()), _ => None })
+            .last()
+            .unwrap();
+        let mut found = vec![];
+        idents(errors_body, &mut found);
+        assert!(!found.is_empty());
+        for (ident, line) in found {
+            assert_eq!(line, 2, "'{}' was not spanned at the quick! entry", ident);
+        }
+    }
+
+    #[test]
+    pub fn test_crate_option() {
+        let input = quote!{
+            crate = ::my_error_chain;
+            errors {
+                quick!(QuickError1, "Error 1 Description: Zero arguments")
+            }
+        };
+        let output = quick::main(input).unwrap();
+        let expected_output = quote!{
+            ::my_error_chain::error_chain!{
+                errors {
+                    QuickError1 {
+                        description("Error 1 Description: Zero arguments")
+                        display("Error 1 Description: Zero arguments")
+                    }
+                }
+            }
+        };
+        assert_eq!(output.to_string(),expected_output.to_string());
+        let err = quick::main(quote!{ crate = ::a; crate = ::b; errors { quick!(A, "A") } }).unwrap_err();
+        assert_eq!(err.to_string(),"The 'crate' option was already given");
+        let err = quick::main(quote!{ errors { quick!(A, "A") } crate = ::a; }).unwrap_err();
+        assert_eq!(err.to_string(),"Options must be given before any section");
+    }
+
+    #[test]
+    pub fn test_thiserror_backend() {
+        let input = quote!{
+            backend = thiserror;
+            types {
+                BuildError, BEKind, BETrait;
+            }
+            foreign_links {
+                Io(::std::io::Error);
+            }
+            errors {
+                quick!(QuickError1, "Error 1 Description: One Argument",(arg1,))
+            }
+            skip_msg_variant
+        };
+        let output = quick::main(input).unwrap();
+        let expected_output = quote!{
+            /// The Error type.
+            #[derive(Debug, ::thiserror::Error)]
+            pub enum BuildError {
+                #[error(transparent)]
+                Io(#[from] ::std::io::Error),
+                #[error("Error 1 Description: One Argument: {}", .0)]
+                QuickError1(String),
+            }
+
+            /// Alias of the error type, for code written against `error_chain!` kinds.
+            #[allow(unused)]
+            pub type BEKind = BuildError;
+        };
+        assert_eq!(output.to_string(),expected_output.to_string());
+        let err = quick::main(quote!{ backend = thiserorr; errors { quick!(A, "A") } }).unwrap_err();
+        assert_eq!(err.to_string(),"Unknown backend 'thiserorr', did you mean 'thiserror'?");
+        let err = quick::main(quote!{
+            backend = thiserror;
+            errors { A(x: u8) { display("{}", x) -> ("A") } }
+        }).unwrap_err();
+        assert_eq!(err.to_string(),"The 'display(..) -> (..)' form is only supported by the error_chain backend");
+    }
+
+    #[test]
+    pub fn test_std_backend() {
+        let output = quick::main(quote!{
+            backend = std;
+            errors {
+                quick!(QuickError1, "Error 1 Description: One Argument",(arg1,))
+            }
+        }).unwrap().to_string();
+        let expected_variant = quote!{ pub enum ErrorKind { QuickError1(String), /// A convenient variant for String.
+            Msg(String), } };
+        let expected_arm = quote!{ ErrorKind::QuickError1(ref arg1) => write!(fmt, "Error 1 Description: One Argument: {}", arg1), };
+        assert!(output.contains(&expected_variant.to_string()), "{}", output);
+        assert!(output.contains(&expected_arm.to_string()), "{}", output);
+        assert!(output.contains(&quote!(pub trait ResultExt<T>).to_string()), "{}", output);
+        assert!(!output.contains("error_chain"), "{}", output);
+    }
+
+    #[test]
+    pub fn test_snafu_backend() {
+        let input = quote!{
+            backend = snafu;
+            foreign_links {
+                Io(::std::io::Error);
+            }
+            errors {
+                quick!(QuickError1, "Error 1 Description: One Argument",(arg1,))
+            }
+            skip_msg_variant
+        };
+        let output = quick::main(input).unwrap();
+        let expected_output = quote!{
+            /// The Error type.
+            #[derive(Debug, ::snafu::Snafu)]
+            #[snafu(crate_root(::snafu), visibility(pub))]
+            pub enum Error {
+                #[snafu(context(false), display("{}", source))]
+                Io { source: ::std::io::Error },
+                #[snafu(display("Error 1 Description: One Argument: {}", arg1))]
+                QuickError1 { arg1: String },
+            }
+
+            /// Alias of the error type, for code written against `error_chain!` kinds.
+            #[allow(unused)]
+            pub type ErrorKind = Error;
+
+            /// Convenient wrapper around `std::Result`.
+            #[allow(unused)]
+            pub type Result<T> = ::std::result::Result<T, Error>;
+        };
+        assert_eq!(output.to_string(),expected_output.to_string());
+    }
+
+    #[test]
+    pub fn test_ast() {
+        use quote::ToTokens;
+        use crate::quick::{ErrorsChildElementEnum, RootElementEnum};
+        use crate::quick::errors_child_element::ErrorItem;
+        let input = quote!{
+            backend = thiserror;
+            types {
+                BuildError, BEKind, BETrait;
+            }
+            errors {
+                #[doc = "First error"]
+                NormalError1 (code: u32, name: String) {
+                    description("Error 1 Description"),
+                    display("Error 1 Display: {}, {}", code, name),
+                }
+                #[doc = "Second error"]
+                quick!(QuickError1, "Error 2 Description", (arg1))
+                quick!(QuickError2, "Error 3 Description",)
+                quick!(QuickError3, "Error 4 Description", (arg1),)
+            }
+        };
+        let parsed = quick::parse(input.clone()).unwrap();
+        assert!(format!("{:?}", parsed).starts_with("RootElementVec { options: Options {"));
+        assert_eq!(parsed.to_token_stream().to_string(), input.to_string());
+        let reparsed = quick::parse(parsed.to_token_stream()).unwrap();
+        assert_eq!(reparsed.to_token_stream().to_string(), input.to_string());
+
+        let errors = match parsed.items().last().unwrap() {
+            RootElementEnum::ErrorsIdGroup(val) => val.items(),
+            other => panic!("Expected the errors section, found {:?}", other)
+        };
+        let normal = match errors[0] {
+            ErrorsChildElementEnum::NormalError(ref val) => val,
+            ref other => panic!("Expected a normal error, found {:?}", other)
+        };
+        assert_eq!(normal.ident().to_string(), "NormalError1");
+        assert_eq!(normal.attrs().len(), 1);
+        let args: Vec<String> = normal.args().unwrap().iter()
+            .map(|arg| format!("{}: {}", arg.ident, arg.ty.to_token_stream()))
+            .collect();
+        assert_eq!(args, vec!["code: u32", "name: String"]);
+        assert_eq!(normal.description().unwrap().expr.to_token_stream().to_string(), "\"Error 1 Description\"");
+        assert_eq!(normal.display().unwrap().args.len(), 3);
+        assert!(matches!(normal.items().next(), Some(ErrorItem::Description(_))));
+        let quick = match errors[1] {
+            ErrorsChildElementEnum::QuickError(ref val) => val,
+            ref other => panic!("Expected a quick! error, found {:?}", other)
+        };
+        assert_eq!(quick.description().value(), "Error 2 Description");
+        assert_eq!(quick.args().len(), 1);
+        assert_eq!(quick.attrs().len(), 1);
+        assert_eq!(quick.clone().into_normal().attrs().len(), 1);
+
+        let err = quick::main(quote!{ errors { A { descripton("A") } } }).unwrap_err();
+        assert_eq!(err.to_string(),"Unknown error item 'descripton', did you mean 'description'?");
+    }
+}
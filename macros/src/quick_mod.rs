@@ -0,0 +1,197 @@
+//! This module implements the `#[error_chain_quick_mod]` attribute for `error-chain-utils`
+//! See the full documentation there
+
+use proc_macro2::{Delimiter, Group, Ident, Span, TokenStream, TokenTree};
+use syn::{Item, ItemMacro, ItemMod, MacroDelimiter, parse::Parser, parse2};
+use quote::{quote, ToTokens};
+use crate::quick::{self, RootElementVec};
+use crate::options::Options;
+
+
+fn macro_group(mac: &ItemMacro) -> Group {
+    let (delimiter, span) = match mac.mac.delimiter {
+        MacroDelimiter::Paren(ref val) => (Delimiter::Parenthesis, val.span),
+        MacroDelimiter::Brace(ref val) => (Delimiter::Brace, val.span),
+        MacroDelimiter::Bracket(ref val) => (Delimiter::Bracket, val.span)
+    };
+    let mut group = Group::new(delimiter, mac.mac.tokens.clone());
+    group.set_span(span);
+    group
+}
+
+/// Parses the definitions among the items of a module, `attr` being the arguments of the
+/// attribute, and returns them along with the other items
+fn split_items(attr: TokenStream, items: Vec<Item>) -> syn::Result<(RootElementVec, Vec<Item>)> {
+    let options = Options::parse_list.parse2(attr)?;
+    let mut sections = TokenStream::new();
+    let mut errors = TokenStream::new();
+    let mut errors_ident: Option<Ident> = None;
+    let mut kept_items = vec![];
+    for item in items {
+        let mac = match item {
+            Item::Macro(val) if val.ident.is_none() => val,
+            other => {
+                kept_items.push(other);
+                continue;
+            }
+        };
+        let ident = match mac.mac.path.get_ident() {
+            Some(val) => val.clone(),
+            None => {
+                kept_items.push(Item::Macro(mac));
+                continue;
+            }
+        };
+        let name = ident.to_string();
+        let is_shorthand = quick::SHORTHANDS.contains(&name.as_str());
+        // Any other macro is the user's own, misspelled sections included, which rustc reports as unknown macros
+        if !is_shorthand && !quick::SECTIONS.contains(&name.as_str()) {
+            kept_items.push(Item::Macro(mac));
+            continue;
+        }
+        if is_shorthand {
+            // Attributes of entries, such as doc comments, are given to their variant
+            for attr in &mac.attrs {
+                attr.to_tokens(&mut errors);
+            }
+            mac.mac.path.to_tokens(&mut errors);
+            mac.mac.bang_token.to_tokens(&mut errors);
+            errors.extend(Some(TokenTree::from(macro_group(&mac))));
+        } else if let Some(attr) = mac.attrs.first() {
+            return Err(syn::Error::new_spanned(attr, "Attributes are not supported on error_chain sections"));
+        } else if name == "errors" {
+            errors_ident.get_or_insert(ident);
+            errors.extend(mac.mac.tokens);
+        } else {
+            sections.extend(Some(TokenTree::from(ident)));
+            if !mac.mac.tokens.is_empty() {
+                let group = macro_group(&mac);
+                let mut body = Group::new(Delimiter::Brace, group.stream());
+                body.set_span(group.span());
+                sections.extend(Some(TokenTree::from(body)));
+            }
+        }
+    }
+    if !errors.is_empty() {
+        let ident = errors_ident.unwrap_or_else(|| Ident::new("errors", Span::call_site()));
+        sections.extend(Some(TokenTree::from(ident)));
+        sections.extend(Some(TokenTree::from(Group::new(Delimiter::Brace, errors))));
+    }
+
+    let mut parsed = quick::parse(sections)?;
+    parsed.set_options(options);
+    Ok((parsed, kept_items))
+}
+
+/// Parses the definitions of an `#[error_chain_quick_mod]` module, `attr` being the arguments of the attribute
+pub fn parse_module(attr: TokenStream, module: &ItemMod) -> syn::Result<RootElementVec> {
+    match module.content {
+        Some((_, ref items)) => Ok(split_items(attr, items.clone())?.0),
+        None => Err(syn::Error::new(module.ident.span(), "Expected a module with a body"))
+    }
+}
+
+/// Main function for the [`error_chain_quick_mod`](../../error_chain_utils/attr.error_chain_quick_mod.html) attribute
+pub fn main(attr: TokenStream, item: TokenStream) -> syn::Result<TokenStream> {
+    let mut module: ItemMod = parse2(item)?;
+    let (brace, items) = match module.content.take() {
+        Some(val) => val,
+        None => return Err(syn::Error::new(module.ident.span(), "Expected a module with a body"))
+    };
+    let (parsed, mut kept_items) = split_items(attr, items)?;
+    kept_items.push(Item::Verbatim(quick::expand(parsed)?));
+    module.content = Some((brace, kept_items));
+
+    let vis = &module.vis;
+    let ident = &module.ident;
+    Ok(quote!{
+        #module
+        #[allow(unused_imports)]
+        #vis use #ident::*;
+    })
+}
+
+
+#[cfg(test)]
+mod tests{
+    use std::assert_eq;
+    use quote::quote;
+    use crate::{quick, quick_mod};
+    #[test]
+    pub fn test() {
+        let attr_input = quote!{
+            pub mod errors {
+                use std::io;
+
+                types! {
+                    BuildError, BEKind, BETrait, BEResult;
+                }
+                foreign_links! {
+                    Io(io::Error);
+                }
+                quick!(QuickError1, "Error 1 Description: Zero arguments");
+                errors! {
+                    NormalError1 {
+                        description("Error 1 Description: Without Arguments"),
+                        display("Error 1 Display")
+                    }
+                }
+                quick!(QuickError2, "Error 2 Description: One Argument",(arg1,));
+            }
+        };
+        let function_input = quote!{
+            types {
+                BuildError, BEKind, BETrait, BEResult;
+            }
+            foreign_links {
+                Io(io::Error);
+            }
+            errors {
+                quick!(QuickError1, "Error 1 Description: Zero arguments")
+                NormalError1 {
+                    description("Error 1 Description: Without Arguments"),
+                    display("Error 1 Display")
+                }
+                quick!(QuickError2, "Error 2 Description: One Argument",(arg1,))
+            }
+        };
+        let output = quick_mod::main(quote!(), attr_input).unwrap();
+        let expanded = quick::main(function_input).unwrap();
+        let expected_output = quote!{
+            pub mod errors {
+                use std::io;
+                #expanded
+            }
+            #[allow(unused_imports)]
+            pub use errors::*;
+        };
+        assert_eq!(output.to_string(),expected_output.to_string(),"Actual output and Expected output did not match.\n Expected Output: \n{:#?}\n Actual Output: \n{:#?}\n",expected_output,output);
+    }
+
+    #[test]
+    pub fn test_user_macros() {
+        // Macros named like sections or shorthands, but not exactly, are left alone
+        let output = quick_mod::main(quote!(), quote!{
+            mod errors {
+                error!(NotASection);
+                quirk!(NotAShorthand);
+                quick!(QuickError1, "Error 1 Description");
+            }
+        }).unwrap();
+        let expanded = quick::main(quote!{
+            errors {
+                quick!(QuickError1, "Error 1 Description")
+            }
+        }).unwrap();
+        let expected_output = quote!{
+            mod errors {
+                error!(NotASection);
+                quirk!(NotAShorthand);
+                #expanded
+            }
+            #[allow(unused_imports)]
+            use errors::*;
+        };
+        assert_eq!(output.to_string(),expected_output.to_string());
+    }
+}
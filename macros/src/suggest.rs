@@ -0,0 +1,42 @@
+//! Helpers for "did you mean" style diagnostics
+
+/// Optimal string alignment distance between two strings, so that a swapped
+/// pair of letters counts as a single edit
+fn distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    rows[0] = (0..=b.len()).collect();
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            let mut best = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+/// Returns the candidate closest to `name`, if any is close enough to be a likely typo
+pub(crate) fn closest<'a>(name: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let lowercase = name.to_lowercase();
+    let mut best: Option<(usize, &'a str)> = None;
+    for candidate in candidates {
+        if *candidate == name {
+            continue;
+        }
+        let dist = if candidate.to_lowercase() == lowercase { 0 } else { distance(&lowercase, candidate) };
+        if dist <= candidate.len().max(3) / 3 && !matches!(best, Some((best_dist, _)) if best_dist <= dist) {
+            best = Some((dist, candidate));
+        }
+    }
+    best.map(|(_, candidate)| candidate)
+}
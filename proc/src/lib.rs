@@ -0,0 +1,32 @@
+//! Procedural macros for `error-chain-utils`
+//!
+//! These macros expand to paths through the `error-chain-utils` crate, so they
+//! should be used through it rather than by depending on this crate directly.
+
+use error_chain_utils_lib::from_file::main as ecff_main;
+use error_chain_utils_lib::quick::main as ecq_main;
+use error_chain_utils_lib::quick_mod::main as ecqm_main;
+
+#[proc_macro]
+pub fn error_chain_quick(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    match ecq_main(input.into()) {
+        Ok(val) => val,
+        Err(e) => e.into_compile_error()
+    }.into()
+}
+
+#[proc_macro_attribute]
+pub fn error_chain_quick_mod(attr: proc_macro::TokenStream, item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    match ecqm_main(attr.into(), item.into()) {
+        Ok(val) => val,
+        Err(e) => e.into_compile_error()
+    }.into()
+}
+
+#[proc_macro]
+pub fn error_chain_from_file(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    match ecff_main(input.into()) {
+        Ok(val) => val,
+        Err(e) => e.into_compile_error()
+    }.into()
+}
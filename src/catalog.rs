@@ -0,0 +1,32 @@
+//! Types of the `ERROR_CATALOG` static emitted by `error_chain_quick!` with `catalog = true;`
+//!
+//! The catalog lists the errors as written in the definitions, for rendering
+//! documentation or checking that every error is covered somewhere.
+
+/// An error of the `errors` section, with its messages and `#[catalog(...)]` metadata
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorInfo {
+    /// Name of the variant
+    pub name: &'static str,
+    /// The description, as written in the definitions
+    pub description: &'static str,
+    /// Format string of the display, the description when the error has none
+    pub display: &'static str,
+    /// Arguments of the variant, in order
+    pub args: &'static [ArgInfo],
+    /// `code` of the `#[catalog(...)]` attribute
+    pub code: Option<&'static str>,
+    /// `severity` of the `#[catalog(...)]` attribute, such as `"error"` or `"warning"`
+    pub severity: Option<&'static str>,
+    /// `help` of the `#[catalog(...)]` attribute, a hint on how to fix the error
+    pub help: Option<&'static str>
+}
+
+/// An argument of an error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArgInfo {
+    /// Name of the argument
+    pub name: &'static str,
+    /// The type, as written in the definitions
+    pub ty: &'static str
+}
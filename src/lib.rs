@@ -6,11 +6,18 @@
 //! So far, these utilities are available:
 //!
 //! - `error_chain_quick`: Extension for convenience to the `error-chain` crate
+//! - `error_chain_quick_mod`: Attribute form of `error_chain_quick`, applied to a module
+//! - `error_chain_from_file`: Form of `error_chain_quick` reading the definitions from a TOML or YAML file
+//! - `catalog`: Types of the `ERROR_CATALOG` static `error_chain_quick` can emit
 //!
 
-use proc_macro;
-use error_chain_utils_lib::quick::main as ecq_main;
+pub mod catalog;
 
+/// The version of `error-chain` the macros of this crate expand to
+///
+/// Generated code refers to `error-chain` through this re-export, so crates
+/// using these macros don't need to depend on it themselves.
+pub use error_chain;
 
 /// Utility that expands to `error_chain!{...}`
 ///
@@ -20,7 +27,6 @@ use error_chain_utils_lib::quick::main as ecq_main;
 ///
 /// // Example of input
 /// ```
-/// #[allow(unused_imports)]
 /// use error_chain_utils::error_chain_quick;
 /// error_chain_quick!{
 ///     types {
@@ -36,8 +42,7 @@ use error_chain_utils_lib::quick::main as ecq_main;
 /// 
 /// // Which would be processed into the following
 /// ```
-/// use error_chain::error_chain;
-/// error_chain!{
+/// ::error_chain_utils::error_chain::error_chain!{
 ///     types {
 ///         CustomError, CustomErrorEnum, CustomErrorTrait, CustomErrorResult;
 ///     }
@@ -60,14 +65,262 @@ use error_chain_utils_lib::quick::main as ecq_main;
 /// 
 /// Normal errors and `quick!` macro errors are supported in the same `errors` block.
 /// 
-/// Probably due to the double-expansion needed to make this happen, Rust considers
-/// this macro unused, even when it actually is being used. To bypass the diagnostics
-/// stemming from this, add #[allow(unused_imports)] before the import statement, as shown above.
+/// The `types`, `links`, `foreign_links` and `skip_msg_variant` sections are checked
+/// before expanding, so typos in section names or missing semicolons are reported
+/// on the offending tokens instead of deep inside `error_chain!`.
+/// 
+/// The expansion goes through the [`error_chain`] re-export
+/// of this crate, so there is no need to depend on `error-chain` directly.
+/// 
+/// To use a renamed or vendored `error-chain` instead, give its path with the
+/// `crate` option, before any section:
+///
+/// ```
+/// use error_chain_utils::error_chain_quick;
+/// error_chain_quick!{
+///     crate = ::error_chain;
+///
+///     errors {
+///         quick!(ErrWithoutArgs, "Error Without Arguments")
+///     }
+/// }
+/// ```
+///
+/// The same definitions can be expanded into an enum deriving `thiserror::Error`
+/// instead, with the `backend` option. Errors become variants of the type named
+/// in `types` (`Error` by default), the kind name becomes an alias of it, and
+/// links and foreign links become transparent variants with a `From` impl.
+/// The `display(..) -> (..)` form is not supported by this backend, and crates
+/// using it need to depend on `thiserror` themselves.
+///
+/// ```
+/// use error_chain_utils::error_chain_quick;
+/// error_chain_quick!{
+///     backend = thiserror;
+///
+///     foreign_links {
+///         Fmt(::std::fmt::Error);
+///     }
+///
+///     errors {
+///         quick!(ErrWithArgs, "Error With Arguments", (arg1,arg2))
+///     }
+/// }
+///
+/// let err = Error::ErrWithArgs("a".to_string(), "b".to_string());
+/// assert_eq!(err.to_string(), "Error With Arguments: a, b");
+/// ```
+///
+/// With `backend = std;`, the definitions expand into plain `ErrorKind` and `Error`
+/// types and a `ResultExt` trait with `chain_err`, using nothing but the standard
+/// library. The error keeps the chained error as its `source`, and links hold the
+/// whole linked error instead of its kind. There is no backtrace support.
+///
+/// ```
+/// use error_chain_utils::error_chain_quick;
+/// error_chain_quick!{
+///     backend = std;
+///
+///     errors {
+///         quick!(ErrWithArgs, "Error With Arguments", (arg1,))
+///     }
+/// }
+///
+/// let result: Result<()> = Err(::std::fmt::Error)
+///     .chain_err(|| ErrorKind::ErrWithArgs("a".to_string()));
+/// assert_eq!(result.unwrap_err().to_string(), "Error With Arguments: a");
+/// ```
+///
+/// With `backend = snafu;`, the definitions expand into an enum deriving
+/// `snafu::Snafu`, with public context selectors. Error arguments become named
+/// context fields, and links and foreign links become variants with a `source`
+/// field that convert from the linked error without a context selector. Crates
+/// using it need to depend on `snafu` 0.7 or later themselves.
+///
+/// ```
+/// use error_chain_utils::error_chain_quick;
+/// error_chain_quick!{
+///     backend = snafu;
+///
+///     errors {
+///         quick!(ErrWithArgs, "Error With Arguments", (arg1,))
+///     }
+/// }
+///
+/// let result: Result<()> = ErrWithArgsSnafu { arg1: "a" }.fail();
+/// assert_eq!(result.unwrap_err().to_string(), "Error With Arguments: a");
+/// ```
+///
+/// With `catalog = true;`, a `pub static ERROR_CATALOG` is emitted next to the
+/// definitions, listing every error of the `errors` section as a
+/// [`catalog::ErrorInfo`]. Errors can be given a code, a severity and a help text
+/// with a `#[catalog(...)]` attribute, every key being optional. The attribute is
+/// accepted, and left out of the expansion, whether the catalog is emitted or not.
+///
+/// The catalog refers to these types through `::error_chain_utils::catalog`. When this
+/// crate is renamed, or the definitions are expanded on behalf of another crate, give
+/// the path of the module with the `catalog_path` option.
+///
+/// ```
+/// use error_chain_utils::error_chain_quick;
+/// error_chain_quick!{
+///     catalog = true;
+///
+///     errors {
+///         #[catalog(code = "E0001", severity = "warning", help = "Check the path")]
+///         quick!(NotFound, "Not found", (path,))
+///     }
+/// }
+///
+/// let info = &ERROR_CATALOG[0];
+/// assert_eq!((info.name, info.display, info.code), ("NotFound", "Not found: {}", Some("E0001")));
+/// assert_eq!((info.args[0].name, info.args[0].ty), ("path", "String"));
+/// ```
+#[doc(inline)]
+pub use error_chain_utils_proc::error_chain_quick;
+
+/// Attribute form of [`error_chain_quick!`](macro.error_chain_quick.html), for use on a module
+///
+/// Each section of the `error_chain!` block is written as an item-like macro
+/// inside the module, which keeps the definitions readable by `rustfmt`.
+/// `quick!` errors can be written directly in the module, and normal errors
+/// go inside an `errors!` block. The module is parsed exactly like the body of
+/// `error_chain_quick!`, so both forms accept the same definitions.
+///
+/// // Example of input
+/// ```
+/// use error_chain_utils::error_chain_quick_mod;
+///
+/// #[error_chain_quick_mod]
+/// pub mod errors {
+///     types! {
+///         CustomError, CustomErrorEnum, CustomErrorTrait, CustomErrorResult;
+///     }
+///
+///     foreign_links! {
+///         Io(::std::io::Error);
+///     }
+///
+///     quick!(ErrWithoutArgs, "Error Without Arguments");
+///     quick!(ErrWithArgs,    "Error With Arguments",  (arg1,arg2));
+///
+///     errors! {
+///         NormalErr {
+///             description("Normal Error")
+///             display("Normal Error")
+///         }
+///     }
+/// }
+/// ```
+///
+/// // Which would be processed into the following
+/// ```
+/// pub mod errors {
+///     ::error_chain_utils::error_chain::error_chain!{
+///         types {
+///             CustomError, CustomErrorEnum, CustomErrorTrait, CustomErrorResult;
+///         }
+///
+///         foreign_links {
+///             Io(::std::io::Error);
+///         }
+///
+///         errors {
+///             ErrWithoutArgs {
+///                 description("Error Without Arguments")
+///                 display("Error Without Arguments")
+///             }
+///             ErrWithArgs (arg1: String, arg2: String){
+///                 description("Error With Arguments")
+///                 display("Error With Arguments: {}, {}", arg1, arg2)
+///             }
+///             NormalErr {
+///                 description("Normal Error")
+///                 display("Normal Error")
+///             }
+///         }
+///     }
+/// }
+/// #[allow(unused_imports)]
+/// pub use errors::*;
+/// ```
+///
+/// Any other items in the module are kept as they are. The generated items are
+/// re-exported from the parent module with the visibility of the module itself.
+///
+/// Options such as `crate` are given as arguments to the attribute, as in
+/// `#[error_chain_quick_mod(crate = ::error_chain)]`.
+///
+/// This attribute cannot share the `error_chain_quick` name, as attribute and
+/// function-like macros live in the same namespace.
+#[doc(inline)]
+pub use error_chain_utils_proc::error_chain_quick_mod;
 
-#[proc_macro]
-pub fn error_chain_quick(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    match ecq_main(input.into()) {
-        Ok(val) => val,
-        Err(e) => e.into_compile_error()
-    }.into()
-}
\ No newline at end of file
+/// Form of [`error_chain_quick!`](macro.error_chain_quick.html) reading the definitions from a TOML or YAML file
+///
+/// The path is relative to the directory of the crate's `Cargo.toml`, and the crate
+/// is rebuilt whenever the file changes. Errors in the file are reported with the
+/// line and column they were found at.
+///
+/// // With `tests/errors.toml` holding
+/// ```toml
+/// [types]
+/// error = "FileError"
+/// error_kind = "FileErrorKind"
+/// result_ext = "FileResultExt"
+/// result = "FileResult"
+///
+/// [[foreign_links]]
+/// name = "Fmt"
+/// error = "::std::fmt::Error"
+///
+/// [[errors]]
+/// name = "NotFound"
+/// description = "Not found"
+/// args = ["path"]
+/// code = "E0001"
+///
+/// [[errors]]
+/// name = "Custom"
+/// description = "Custom"
+/// display = "Custom error {} ({})"
+/// args = ["code: u32", "name"]
+/// ```
+///
+/// // The following is the same as the `error_chain_quick!` invocation below it
+/// ```
+/// use error_chain_utils::error_chain_from_file;
+/// error_chain_from_file!("tests/errors.toml");
+/// ```
+///
+/// ```
+/// use error_chain_utils::error_chain_quick;
+/// error_chain_quick!{
+///     types {
+///         FileError, FileErrorKind, FileResultExt, FileResult;
+///     }
+///
+///     foreign_links {
+///         Fmt(::std::fmt::Error);
+///     }
+///
+///     errors {
+///         /// Error code: `E0001`
+///         NotFound (path: String) {
+///             description("Not found")
+///             display("Not found: {}", path)
+///         }
+///         Custom (code: u32, name: String) {
+///             description("Custom")
+///             display("Custom error {} ({})", code, name)
+///         }
+///     }
+/// }
+/// ```
+///
+/// YAML files, with a `.yaml` or `.yml` extension, hold the same fields. Each error
+/// takes a `name` and a `description`, and optionally `args`, a `display` format
+/// given the arguments in order, and a `code`, `severity` and `help` listed in the
+/// catalog. Options such as `backend` and `catalog` are top level fields.
+#[doc(inline)]
+pub use error_chain_utils_proc::error_chain_from_file;
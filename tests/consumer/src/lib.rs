@@ -0,0 +1,47 @@
+//! A crate using `error-chain-utils` without depending on `error-chain` itself
+
+use error_chain_utils::{error_chain_quick, error_chain_quick_mod};
+use error_chain_utils::error_chain::bail;
+
+error_chain_quick!{
+    types {
+        AppError, AppErrorKind, AppResultExt, AppResult;
+    }
+
+    foreign_links {
+        Io(::std::io::Error);
+    }
+
+    errors {
+        quick!(NotFound, "Not found", (path,))
+    }
+}
+
+#[error_chain_quick_mod]
+pub mod module_errors {
+    quick!(Denied, "Denied");
+}
+
+/// Always fails, with a chained error when `path` is not empty
+pub fn open(path: &str) -> AppResult<()> {
+    if path.is_empty() {
+        bail!(AppErrorKind::NotFound(path.to_string()));
+    }
+    let denied: module_errors::Result<()> = Err(module_errors::ErrorKind::Denied.into());
+    AppResultExt::chain_err(denied, || AppErrorKind::NotFound(path.to_string()))
+}
+
+
+#[cfg(test)]
+mod tests{
+    use std::assert_eq;
+    use crate::open;
+
+    #[test]
+    pub fn test_consumer() {
+        assert_eq!(open("").unwrap_err().to_string(), "Not found: ");
+        let err = open("a").unwrap_err();
+        assert_eq!(err.to_string(), "Not found: a");
+        assert_eq!(err.iter().nth(1).unwrap().to_string(), "Denied");
+    }
+}
@@ -1,6 +1,4 @@
 use error_chain_utils::error_chain_quick;
-#[allow(unused_imports)]
-use error_chain::error_chain;
 
 #[test]
 fn expand_macro() {
@@ -23,4 +21,229 @@ fn expand_macro() {
             quick!(QuickError3, "Error 3 Description: Three Arguments",(arg1,arg2,arg3,))
         }
     }
-}
\ No newline at end of file
+}
+
+#[error_chain_utils::error_chain_quick_mod]
+mod module_errors {
+    types! {
+        ModError, MEKind, METrait, MEResult;
+    }
+
+    quick!(QuickError1, "Error 1 Description: Zero arguments");
+    quick!(QuickError2, "Error 2 Description: One Argument",(arg1,));
+
+    errors! {
+        NormalError1 {
+            description("Error 1 Description: Without Arguments"),
+            display("Error 1 Display")
+        }
+    }
+}
+
+#[test]
+fn expand_attribute() {
+    let err: ModError = MEKind::QuickError2("value".to_string()).into();
+    assert_eq!(err.to_string(), "Error 2 Description: One Argument: value");
+}
+
+mod renamed_crate {
+    use error_chain_utils::error_chain_quick;
+
+    error_chain_quick!{
+        crate = ::error_chain;
+
+        errors {
+            quick!(QuickError1, "Error 1 Description: Zero arguments")
+        }
+    }
+
+    #[test]
+    fn expand_with_crate_path() {
+        let err: Error = ErrorKind::QuickError1.into();
+        assert_eq!(err.to_string(), "Error 1 Description: Zero arguments");
+    }
+}
+
+mod thiserror_backend {
+    use error_chain_utils::error_chain_quick;
+
+    error_chain_quick!{
+        backend = thiserror;
+
+        foreign_links {
+            Fmt(::std::fmt::Error);
+        }
+
+        errors {
+            NormalError1 (code: u32) {
+                description("Error 1 Description: With Arguments"),
+                display("Error 1 Display: {}", code),
+            }
+            quick!(QuickError1, "Error 2 Description: One Argument",(arg1,))
+        }
+    }
+
+    #[test]
+    fn expand_with_thiserror() {
+        assert_eq!(ErrorKind::NormalError1(5).to_string(), "Error 1 Display: 5");
+        assert_eq!(Error::QuickError1("value".to_string()).to_string(), "Error 2 Description: One Argument: value");
+        let err: Error = ::std::fmt::Error.into();
+        assert!(matches!(err, Error::Fmt(_)));
+        let err: Error = "message".into();
+        assert_eq!(err.to_string(), "message");
+        let result: Result<()> = Err(err);
+        assert!(result.is_err());
+    }
+}
+
+mod std_backend {
+    use std::error::Error as _;
+    use error_chain_utils::error_chain_quick;
+
+    error_chain_quick!{
+        backend = std;
+
+        foreign_links {
+            Fmt(::std::fmt::Error);
+        }
+
+        errors {
+            NormalError1 (code: u32) {
+                description("Error 1 Description: With Arguments"),
+                display("Error 1 Display: {}", code),
+            }
+            quick!(QuickError1, "Error 2 Description: One Argument",(arg1,))
+        }
+    }
+
+    fn fails() -> Result<()> {
+        Err(::std::fmt::Error)?
+    }
+
+    #[test]
+    fn expand_with_std() {
+        let err: Error = ErrorKind::NormalError1(5).into();
+        assert_eq!(err.to_string(), "Error 1 Display: 5");
+        let err = fails().unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Fmt(_)));
+        assert!(err.source().is_some());
+        let err = fails().chain_err(|| ErrorKind::QuickError1("value".to_string())).unwrap_err();
+        assert_eq!(err.to_string(), "Error 2 Description: One Argument: value");
+        assert_eq!(err.source().unwrap().to_string(), ::std::fmt::Error.to_string());
+        let err = None::<()>.chain_err(|| "message").unwrap_err();
+        assert_eq!(err.to_string(), "message");
+        assert!(err.source().is_none());
+    }
+}
+
+mod snafu_backend {
+    use error_chain_utils::error_chain_quick;
+
+    error_chain_quick!{
+        backend = snafu;
+
+        foreign_links {
+            Fmt(::std::fmt::Error);
+        }
+
+        errors {
+            NormalError1 (code: u32) {
+                description("Error 1 Description: With Arguments"),
+                display("Error 1 Display: {}", code),
+            }
+            quick!(QuickError1, "Error 2 Description: One Argument",(arg1,))
+        }
+    }
+
+    fn fails() -> Result<()> {
+        Err(::std::fmt::Error)?
+    }
+
+    #[test]
+    fn expand_with_snafu() {
+        let err = NormalError1Snafu { code: 5u32 }.build();
+        assert_eq!(err.to_string(), "Error 1 Display: 5");
+        assert!(matches!(fails().unwrap_err(), Error::Fmt { .. }));
+        let err = QuickError1Snafu { arg1: "value" }.fail::<()>().unwrap_err();
+        assert_eq!(err.to_string(), "Error 2 Description: One Argument: value");
+        let err: Error = "message".into();
+        assert_eq!(err.to_string(), "message");
+    }
+}
+
+mod from_toml_file {
+    use error_chain_utils::error_chain_from_file;
+
+    error_chain_from_file!("tests/errors.toml");
+
+    fn fails() -> FileResult<()> {
+        Err(::std::fmt::Error)?
+    }
+
+    #[test]
+    fn expand_from_toml() {
+        let err: FileError = FileErrorKind::NotFound("a".to_string()).into();
+        assert_eq!(err.to_string(), "Not found: a");
+        let err: FileError = FileErrorKind::Custom(5, "b".to_string()).into();
+        assert_eq!(err.to_string(), "Custom error 5 (b)");
+        assert!(matches!(fails().unwrap_err().kind(), FileErrorKind::Fmt(_)));
+    }
+}
+
+mod from_yaml_file {
+    use error_chain_utils::error_chain_from_file;
+
+    error_chain_from_file!("tests/errors.yaml");
+
+    #[test]
+    fn expand_from_yaml() {
+        assert_eq!(Error::NotFound("a".to_string()).to_string(), "Not found: a");
+        assert_eq!((ERROR_CATALOG[0].code, ERROR_CATALOG[0].help), (Some("E0002"), Some("Check the path")));
+    }
+}
+
+mod catalog {
+    use error_chain_utils::error_chain_quick;
+    use error_chain_utils::catalog::{ArgInfo, ErrorInfo};
+
+    error_chain_quick!{
+        catalog = true;
+
+        errors {
+            #[catalog(code = "E0001", severity = "warning")]
+            /// Not found
+            quick!(NotFound, "Not found", (path,))
+            #[catalog(help = "Retry later")]
+            Custom(code: u32) {
+                description("Custom")
+                display("Custom error {}", code)
+            }
+        }
+    }
+
+    #[test]
+    fn expand_catalog() {
+        assert_eq!(ERROR_CATALOG, &[
+            ErrorInfo {
+                name: "NotFound",
+                description: "Not found",
+                display: "Not found: {}",
+                args: &[ArgInfo { name: "path", ty: "String" }],
+                code: Some("E0001"),
+                severity: Some("warning"),
+                help: None
+            },
+            ErrorInfo {
+                name: "Custom",
+                description: "Custom",
+                display: "Custom error {}",
+                args: &[ArgInfo { name: "code", ty: "u32" }],
+                code: None,
+                severity: None,
+                help: Some("Retry later")
+            }
+        ]);
+        let err: Error = ErrorKind::NotFound("a".to_string()).into();
+        assert_eq!(err.to_string(), "Not found: a");
+    }
+}